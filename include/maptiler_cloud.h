@@ -0,0 +1,39 @@
+/* C interface to maptiler-cloud, built with the `ffi` feature. See src/ffi.rs for details. */
+
+#ifndef MAPTILER_CLOUD_H
+#define MAPTILER_CLOUD_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The tile was written to the buffer */
+#define MAPTILER_OK 0
+/* A pointer was null, a string wasn't UTF-8, the tileset is unknown, or the coordinates are invalid */
+#define MAPTILER_INVALID_ARGUMENT -1
+/* The request failed, ex: the server couldn't be reached, or returned an HTTP error */
+#define MAPTILER_REQUEST_FAILED -2
+/* The buffer is too small for the tile, and `written` holds the size that is needed */
+#define MAPTILER_BUFFER_TOO_SMALL -3
+
+typedef struct MaptilerSession MaptilerSession;
+
+/* Creates a new session with the given API key, returning NULL if it could not be created */
+MaptilerSession *maptiler_session_new(const char *api_key);
+
+/* Frees a session created by maptiler_session_new */
+void maptiler_session_free(MaptilerSession *session);
+
+/* Downloads a tile of the tileset with the given endpoint, ex: "satellite", into the buffer */
+int maptiler_fetch_tile(const MaptilerSession *session, const char *endpoint, uint32_t zoom,
+                        uint32_t x, uint32_t y, uint8_t *buffer, size_t buffer_len,
+                        size_t *written);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,246 @@
+//! The HTTP layer that requests are sent through
+//!
+//! By default, sessions send requests with reqwest and wait using tokio timers, through
+//! `ReqwestBackend`. Applications on another async runtime, such as async-std or smol, can
+//! implement [`HttpBackend`] over their own HTTP client and timer, and pass it to
+//! [`MaptilerBuilder::backend`](crate::MaptilerBuilder::backend).
+//!
+//! Without the default `reqwest` feature, the crate doesn't depend on any HTTP client, and a
+//! backend must always be given.
+
+#[cfg(feature = "reqwest")]
+use std::sync::Arc;
+use std::{fmt, future::Future, pin::Pin, time::Duration};
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode};
+
+/// A boxed future, as returned by the methods of [`HttpBackend`] and [`ResponseBody`]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// An HTTP request to be sent by an [`HttpBackend`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// The method of the request, GET or HEAD
+    pub method: Method,
+    /// The full URL of the request, including the API key
+    pub url: String,
+    /// Extra headers to send with the request
+    pub headers: HeaderMap,
+}
+
+/// An HTTP response whose headers have arrived, and whose body can be read in chunks
+pub struct HttpResponse {
+    /// The HTTP status code that the server returned
+    pub status: StatusCode,
+    /// The headers that the server returned
+    pub headers: HeaderMap,
+    /// The body of the response, which has not been read yet
+    pub body: Box<dyn ResponseBody>,
+}
+
+impl fmt::Debug for HttpResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The body of an [`HttpResponse`]
+///
+/// A backend may decompress bodies as they are read, as long as it either removes the
+/// Content-Encoding header or leaves it in place. With the header, the length of the body isn't
+/// checked against Content-Length, which is the length of the compressed body.
+///
+pub trait ResponseBody: Send {
+    /// Returns the next chunk of the body, or None once the whole body has been read
+    fn chunk(&mut self) -> BoxFuture<'_, Result<Option<Bytes>, BackendError>>;
+}
+
+/// An async HTTP client and timer that requests are sent through
+pub trait HttpBackend: Send + Sync {
+    /// Sends a request, resolving once the response headers have arrived
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BackendError>>;
+
+    /// Waits for the given amount of time
+    ///
+    /// This is used to pace requests for quotas and bandwidth limits, and must not block the
+    /// thread
+    ///
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// What kind of failure a [`BackendError`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendErrorKind {
+    /// The server could not be connected to
+    Connect,
+    /// The request or the connection timed out
+    Timeout,
+    /// Any other failure, such as the connection being dropped halfway through the body
+    Other,
+}
+
+impl fmt::Display for BackendErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BackendErrorKind::Connect => "failed to connect",
+            BackendErrorKind::Timeout => "timed out",
+            BackendErrorKind::Other => "request failed",
+        })
+    }
+}
+
+/// An error from an [`HttpBackend`]
+#[derive(Debug, thiserror::Error)]
+#[error("{kind}: {source}")]
+pub struct BackendError {
+    kind: BackendErrorKind,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl BackendError {
+    /// Creates a new BackendError of the given kind, caused by the given error
+    pub fn new<E>(kind: BackendErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self {
+            kind,
+            source: source.into(),
+        }
+    }
+
+    /// Returns what kind of failure this is
+    pub fn kind(&self) -> BackendErrorKind {
+        self.kind
+    }
+
+    /// Returns true if the server could not be connected to
+    pub fn is_connect(&self) -> bool {
+        self.kind == BackendErrorKind::Connect
+    }
+
+    /// Returns true if the request or the connection timed out
+    pub fn is_timeout(&self) -> bool {
+        self.kind == BackendErrorKind::Timeout
+    }
+}
+
+/// The default [`HttpBackend`], which uses [`reqwest`] and [`tokio`]
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone)]
+pub struct ReqwestBackend {
+    client: Arc<reqwest::Client>,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestBackend {
+    /// Creates a new ReqwestBackend that sends requests with the given client
+    pub fn new(client: Arc<reqwest::Client>) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpBackend for ReqwestBackend {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BackendError>> {
+        Box::pin(async move {
+            let res = self
+                .client
+                .request(request.method, request.url)
+                .headers(request.headers)
+                .send()
+                .await
+                .map_err(from_reqwest)?;
+
+            Ok(HttpResponse {
+                status: res.status(),
+                headers: res.headers().clone(),
+                body: Box::new(ReqwestBody(res)),
+            })
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// An [`HttpBackend`] that sends requests over HTTP/3, and falls back to HTTP/2 or HTTP/1.1 if the
+/// server can't be reached that way
+///
+/// UDP is blocked on many networks, so once an HTTP/3 request fails to connect or times out, the
+/// backend sticks to the fallback client for the rest of the session.
+///
+/// This is what [`MaptilerBuilder::http3`](crate::MaptilerBuilder::http3) uses
+///
+#[cfg(feature = "http3")]
+#[derive(Debug)]
+pub struct Http3Backend {
+    http3: ReqwestBackend,
+    fallback: ReqwestBackend,
+    http3_failed: std::sync::atomic::AtomicBool,
+}
+
+#[cfg(feature = "http3")]
+impl Http3Backend {
+    /// Creates a new Http3Backend from a client that only speaks HTTP/3, see
+    /// [`reqwest::ClientBuilder::http3_prior_knowledge`], and a client to fall back to
+    pub fn new(http3: Arc<reqwest::Client>, fallback: Arc<reqwest::Client>) -> Self {
+        Self {
+            http3: ReqwestBackend::new(http3),
+            fallback: ReqwestBackend::new(fallback),
+            http3_failed: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+}
+
+#[cfg(feature = "http3")]
+impl HttpBackend for Http3Backend {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BackendError>> {
+        use std::sync::atomic::Ordering;
+
+        Box::pin(async move {
+            if !self.http3_failed.load(Ordering::Relaxed) {
+                match self.http3.send(request.clone()).await {
+                    Err(e) if e.is_connect() || e.is_timeout() => {
+                        self.http3_failed.store(true, Ordering::Relaxed);
+                    }
+                    result => return result,
+                }
+            }
+
+            self.fallback.send(request).await
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.fallback.sleep(duration)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+struct ReqwestBody(reqwest::Response);
+
+#[cfg(feature = "reqwest")]
+impl ResponseBody for ReqwestBody {
+    fn chunk(&mut self) -> BoxFuture<'_, Result<Option<Bytes>, BackendError>> {
+        Box::pin(async move { self.0.chunk().await.map_err(from_reqwest) })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn from_reqwest(e: reqwest::Error) -> BackendError {
+    let kind = if e.is_connect() {
+        BackendErrorKind::Connect
+    } else if e.is_timeout() {
+        BackendErrorKind::Timeout
+    } else {
+        BackendErrorKind::Other
+    };
+
+    BackendError::new(kind, e)
+}
@@ -0,0 +1,139 @@
+//! A command line interface to the Maptiler Cloud API
+//!
+//! The API key is read from the MAPTILER_KEY environment variable, or from the --key argument.
+//! Other session settings can be loaded from a configuration file with --config.
+
+use std::{env, io::Write, process};
+
+use maptiler_cloud::{errors::Error, Config, Maptiler, TileJsonRequest, TileRequest, TileSet};
+
+const USAGE: &str = "\
+Usage: maptiler-cloud [--key <API KEY>] [--config <FILE>] <COMMAND>
+
+Commands:
+    tile <TILESET> <Z> <X> <Y> [-o <FILE>]    Downloads a single tile
+    tilejson <TILESET>                        Prints the TileJSON metadata of a tileset
+
+TILESET is the Maptiler Cloud endpoint of the tileset, ex: satellite
+
+If no output file is given, tiles are written to <TILESET>-<Z>-<X>-<Y>.<EXT>";
+
+#[tokio::main]
+async fn main() {
+    if let Err(message) = run(env::args().skip(1).collect()).await {
+        eprintln!("error: {}", message);
+        eprintln!();
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
+
+async fn run(mut args: Vec<String>) -> Result<(), String> {
+    let api_key = take_option(&mut args, "--key")?;
+
+    let mut config = match take_option(&mut args, "--config")? {
+        Some(path) => Config::from_file(&path).map_err(|e| format!("{}: {}", path, e))?,
+        None => Config::default(),
+    };
+
+    // A key given on the command line always wins over the configuration
+    if api_key.is_some() {
+        config.api_key = api_key;
+    }
+
+    let maptiler = Maptiler::from_config(&config).map_err(|e| e.to_string())?;
+
+    if args.is_empty() {
+        return Err(String::from("no command given"));
+    }
+
+    let command = args.remove(0);
+
+    match command.as_str() {
+        "tile" => tile(&maptiler, args).await,
+        "tilejson" => tilejson(&maptiler, args).await,
+        _ => Err(format!("unknown command `{}`", command)),
+    }
+}
+
+async fn tile(maptiler: &Maptiler, mut args: Vec<String>) -> Result<(), String> {
+    let output = take_option(&mut args, "-o")?;
+
+    let (set, zoom, x, y) = match args.as_slice() {
+        [set, zoom, x, y] => (
+            parse_tileset(set)?,
+            parse_number(zoom, "Z")?,
+            parse_number(x, "X")?,
+            parse_number(y, "Y")?,
+        ),
+        _ => return Err(String::from("tile expects <TILESET> <Z> <X> <Y>")),
+    };
+
+    let tile_request = TileRequest::new(set, x, y, zoom).map_err(|e| e.to_string())?;
+
+    let output = output.unwrap_or_else(|| {
+        format!(
+            "{}-{}-{}-{}.{}",
+            set.endpoint(),
+            zoom,
+            x,
+            y,
+            set.file_extension()
+        )
+    });
+
+    maptiler
+        .create_request(tile_request)
+        .execute_to_file(&output)
+        .await
+        .map_err(|e| match e {
+            Error::Io(e) => format!("failed to write {}: {}", output, e),
+            e => e.to_string(),
+        })
+}
+
+async fn tilejson(maptiler: &Maptiler, args: Vec<String>) -> Result<(), String> {
+    let set = match args.as_slice() {
+        [set] => parse_tileset(set)?,
+        _ => return Err(String::from("tilejson expects <TILESET>")),
+    };
+
+    let bytes = maptiler
+        .create_request(TileJsonRequest::new(set))
+        .execute()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut stdout = std::io::stdout();
+    stdout
+        .write_all(&bytes)
+        .and_then(|_| writeln!(stdout))
+        .map_err(|e| e.to_string())
+}
+
+// Removes an option and its value from the arguments, if it is present
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let position = match args.iter().position(|arg| arg == name) {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+
+    if position + 1 >= args.len() {
+        return Err(format!("{} expects a value", name));
+    }
+
+    let value = args.remove(position + 1);
+    args.remove(position);
+
+    Ok(Some(value))
+}
+
+fn parse_tileset(endpoint: &str) -> Result<TileSet, String> {
+    TileSet::from_endpoint(endpoint).ok_or_else(|| format!("unknown tileset `{}`", endpoint))
+}
+
+fn parse_number(value: &str, name: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} must be a non-negative integer, got `{}`", name, value))
+}
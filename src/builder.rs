@@ -0,0 +1,357 @@
+use std::{sync::Arc, time::Duration};
+
+#[cfg(feature = "http3")]
+use crate::backend::Http3Backend;
+#[cfg(feature = "reqwest")]
+use crate::backend::ReqwestBackend;
+use crate::{
+    backend::HttpBackend,
+    circuit::{CircuitBreaker, CircuitTracker},
+    errors,
+    pool::BufferPool,
+    quota::{Quota, QuotaTracker},
+    scheduler::{AdaptiveConcurrency, Scheduler},
+    throttle::Throttle,
+    Maptiler,
+};
+
+// The base URL of the Maptiler Cloud API
+const DEFAULT_BASE_URL: &str = "https://api.maptiler.com";
+
+/// A builder for a [`Maptiler`] session, for when the defaults of [`Maptiler::new`] don't fit
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let maptiler = maptiler_cloud::Maptiler::builder("placeholder api key")
+///     .quota(maptiler_cloud::Quota::new(100, Duration::from_secs(60)).wait_for_window())
+///     .build()
+///     .unwrap();
+/// ```
+///
+pub struct MaptilerBuilder {
+    api_key: String,
+    backend: Option<Arc<dyn HttpBackend>>,
+    quota: Option<Quota>,
+    max_bandwidth: Option<u64>,
+    circuit_breaker: Option<CircuitBreaker>,
+    max_concurrent_requests: Option<usize>,
+    adaptive_concurrency: Option<AdaptiveConcurrency>,
+    buffer_pool: Option<usize>,
+    corrupt_tile_retries: u32,
+    max_response_size: Option<u64>,
+    #[cfg(feature = "reqwest")]
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    pinned_certificates: Vec<reqwest::Certificate>,
+    #[cfg(feature = "http3")]
+    http3: bool,
+    base_urls: Vec<String>,
+    dry_run: bool,
+}
+
+impl MaptilerBuilder {
+    pub(crate) fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            backend: None,
+            quota: None,
+            max_bandwidth: None,
+            circuit_breaker: None,
+            max_concurrent_requests: None,
+            adaptive_concurrency: None,
+            buffer_pool: None,
+            corrupt_tile_retries: 0,
+            max_response_size: None,
+            #[cfg(feature = "reqwest")]
+            connect_timeout: None,
+            read_timeout: None,
+            #[cfg(feature = "reqwest")]
+            pinned_certificates: Vec::new(),
+            #[cfg(feature = "http3")]
+            http3: false,
+            base_urls: vec![String::from(DEFAULT_BASE_URL)],
+            dry_run: false,
+        }
+    }
+
+    /// Uses a user provided [`reqwest::Client`] instead of creating a new one
+    #[cfg(feature = "reqwest")]
+    pub fn client(self, client: Arc<reqwest::Client>) -> Self {
+        self.backend(Arc::new(ReqwestBackend::new(client)))
+    }
+
+    /// Sends requests through a user provided [`HttpBackend`] instead of [`reqwest`]
+    ///
+    /// This is the way to use the crate on async runtimes other than tokio
+    ///
+    pub fn backend(mut self, backend: Arc<dyn HttpBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Limits the number of requests that the session may make per time window
+    ///
+    /// The budget is shared by every request created by the session, and by every clone of it.
+    /// See [`Quota`] for details.
+    ///
+    pub fn quota(mut self, quota: Quota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Caps how many bytes per second the session may download
+    ///
+    /// The cap is shared by every request created by the session, and by every clone of it, so
+    /// many tiles downloading at once still won't saturate a metered or shared connection.
+    ///
+    pub fn max_bandwidth(mut self, bytes_per_second: u64) -> Self {
+        self.max_bandwidth = Some(bytes_per_second);
+        self
+    }
+
+    /// Stops sending requests while the API appears to be down, see [`CircuitBreaker`]
+    ///
+    /// The breaker is shared by every request created by the session, and by every clone of it
+    ///
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Limits how many requests of the session may be in flight at once
+    ///
+    /// The limit is shared by every request created by the session, and by every clone of it.
+    /// Requests over the limit wait for a running one to finish, and are started in order of
+    /// their [`Priority`](crate::Priority), see
+    /// [`ConstructedRequest::with_priority`](crate::ConstructedRequest::with_priority). A limit of
+    /// 0 is treated as 1.
+    ///
+    pub fn max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = Some(max_concurrent_requests);
+        self
+    }
+
+    /// Adjusts how many requests of the session may be in flight at once to what the API and the
+    /// connection keep up with, see [`AdaptiveConcurrency`]
+    ///
+    /// Like [`MaptilerBuilder::max_concurrent_requests`], the limit is shared by every request and
+    /// clone of the session, and waiting requests start in order of their priority. This replaces
+    /// a fixed limit if both are set. The current limit is returned by
+    /// [`Maptiler::concurrency_limit`].
+    ///
+    pub fn adaptive_concurrency(mut self, adaptive: AdaptiveConcurrency) -> Self {
+        self.adaptive_concurrency = Some(adaptive);
+        self
+    }
+
+    /// Keeps up to `buffers` emptied response body buffers around for reuse by later requests
+    ///
+    /// Downloading many tiles otherwise allocates a new buffer for every one of them. Tiles are
+    /// handed back with [`Maptiler::recycle`] once they have been written out or decoded, and the
+    /// next response is read into one of them. The pool is shared by every clone of the session.
+    ///
+    pub fn buffer_pool(mut self, buffers: usize) -> Self {
+        self.buffer_pool = Some(buffers);
+        self
+    }
+
+    /// Sets how many times a truncated or corrupt tile is downloaded again before giving up
+    ///
+    /// Tiles are always checked against their reported length and the file format of their
+    /// tileset. By default a corrupt tile is returned as an error right away.
+    ///
+    pub fn corrupt_tile_retries(mut self, retries: u32) -> Self {
+        self.corrupt_tile_retries = retries;
+        self
+    }
+
+    /// Caps how many bytes a single response body may have
+    ///
+    /// Responses that report a larger Content-Length are rejected before their body is read, and
+    /// others are aborted as soon as they grow past the cap. Both fail with
+    /// [`errors::Error::ResponseTooLarge`]. This protects memory-constrained devices from
+    /// unexpectedly huge responses, such as from a misconfigured custom tileset.
+    ///
+    pub fn max_response_size(mut self, bytes: u64) -> Self {
+        self.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Sets how long connecting to the server may take before giving up
+    ///
+    /// This only applies to the [`reqwest::Client`] that the builder creates by default. A client
+    /// passed to [`MaptilerBuilder::client`] should be given its own connect timeout instead.
+    ///
+    /// A timed out connection fails over to the next base URL, see
+    /// [`MaptilerBuilder::base_urls`].
+    ///
+    #[cfg(feature = "reqwest")]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long the server may go without sending any of the response body before giving up
+    ///
+    /// Unlike an overall timeout, this lets large tiles on a slow but steady connection finish,
+    /// while still catching connections that have stalled. The time spent waiting for a
+    /// bandwidth limit doesn't count. This works with any [`HttpBackend`].
+    ///
+    /// A stalled body is returned as a [`BackendError`](crate::backend::BackendError) that
+    /// [`is_timeout`](crate::backend::BackendError::is_timeout).
+    ///
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Only trusts servers whose certificate chain leads to the given root certificate
+    ///
+    /// Once a certificate is pinned, the system root store is no longer trusted, which is useful
+    /// for kiosks and embedded devices that must not rely on it. Call this again to trust more
+    /// than one root, ex: both the current and the next CA of Maptiler Cloud and of any mirrors.
+    ///
+    /// This only applies to the [`reqwest::Client`] that the builder creates by default. A client
+    /// passed to [`MaptilerBuilder::client`] should be given its own root certificates instead.
+    ///
+    #[cfg(feature = "reqwest")]
+    pub fn pin_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.pinned_certificates.push(certificate);
+        self
+    }
+
+    /// Sends requests over HTTP/3 (QUIC), falling back to HTTP/2 if the server can't be reached
+    /// that way
+    ///
+    /// QUIC copes better with lossy links, such as mobile networks. See
+    /// [`Http3Backend`](crate::backend::Http3Backend) for how the fallback works.
+    ///
+    /// This only applies to the [`reqwest::Client`] that the builder creates by default. HTTP/3
+    /// support in reqwest is unstable, so the `http3` feature also needs
+    /// `RUSTFLAGS="--cfg reqwest_unstable"` to build.
+    ///
+    #[cfg(feature = "http3")]
+    pub fn http3(mut self, http3: bool) -> Self {
+        self.http3 = http3;
+        self
+    }
+
+    /// Sets the ordered list of base URLs that requests are sent to, replacing the default of
+    /// "https://api.maptiler.com"
+    ///
+    /// Requests go to the first base URL. If it can't be connected to, or the connection times
+    /// out, the request is sent to the next one, and so on. This allows for an internal caching
+    /// mirror in front of the primary API, or the other way around. Each mirror must serve the
+    /// same paths as Maptiler Cloud, ex: "{base}/tiles/satellite/0/0/0.jpg".
+    ///
+    /// An empty list keeps the default.
+    ///
+    pub fn base_urls<I, S>(mut self, base_urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let base_urls: Vec<String> = base_urls
+            .into_iter()
+            .map(|base_url| base_url.into().trim_end_matches('/').to_string())
+            .collect();
+
+        if !base_urls.is_empty() {
+            self.base_urls = base_urls;
+        }
+
+        self
+    }
+
+    /// Turns on dry-run mode, where requests never touch the network
+    ///
+    /// In dry-run mode, [`ConstructedRequest::execute_or_dry_run`] and
+    /// [`ConstructedRequest::head_or_dry_run`] return [`Execution::DryRun`] holding the request
+    /// that would have been sent, instead of sending it. Quotas and bandwidth limits are not used
+    /// up. Methods that can only return a response, such as [`ConstructedRequest::execute`], fail
+    /// with [`errors::Error::DryRunSession`].
+    ///
+    /// [`ConstructedRequest::execute_or_dry_run`]: crate::ConstructedRequest::execute_or_dry_run
+    /// [`ConstructedRequest::head_or_dry_run`]: crate::ConstructedRequest::head_or_dry_run
+    /// [`ConstructedRequest::execute`]: crate::ConstructedRequest::execute
+    /// [`Execution::DryRun`]: crate::Execution::DryRun
+    ///
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    // Creates the backend used when none was given, over one or two default reqwest clients
+    #[cfg(feature = "reqwest")]
+    fn default_backend(&self) -> Result<Arc<dyn HttpBackend>, errors::Error> {
+        let client = Arc::new(self.client_builder().build()?);
+
+        #[cfg(feature = "http3")]
+        if self.http3 {
+            let http3 = self.client_builder().http3_prior_knowledge().build()?;
+
+            return Ok(Arc::new(Http3Backend::new(Arc::new(http3), client)));
+        }
+
+        Ok(Arc::new(ReqwestBackend::new(client)))
+    }
+
+    #[cfg(feature = "reqwest")]
+    fn client_builder(&self) -> reqwest::ClientBuilder {
+        let mut client = reqwest::Client::builder();
+
+        if let Some(timeout) = self.connect_timeout {
+            client = client.connect_timeout(timeout);
+        }
+
+        if !self.pinned_certificates.is_empty() {
+            client = client.tls_built_in_root_certs(false);
+
+            for certificate in &self.pinned_certificates {
+                client = client.add_root_certificate(certificate.clone());
+            }
+        }
+
+        client
+    }
+
+    #[cfg(not(feature = "reqwest"))]
+    fn default_backend(&self) -> Result<Arc<dyn HttpBackend>, errors::Error> {
+        Err(errors::Error::MissingBackend)
+    }
+
+    /// Builds the Maptiler session
+    pub fn build(self) -> Result<Maptiler, errors::Error> {
+        let backend = match &self.backend {
+            Some(backend) => Arc::clone(backend),
+            None => self.default_backend()?,
+        };
+
+        Ok(Maptiler {
+            api_key: Arc::new(self.api_key),
+            backend,
+            quota: self.quota.map(|quota| Arc::new(QuotaTracker::new(quota))),
+            throttle: self.max_bandwidth.map(|rate| Arc::new(Throttle::new(rate))),
+            circuit: self
+                .circuit_breaker
+                .map(|breaker| Arc::new(CircuitTracker::new(breaker))),
+            scheduler: match (self.adaptive_concurrency, self.max_concurrent_requests) {
+                (Some(adaptive), _) => Some(Arc::new(Scheduler::adaptive(adaptive))),
+                (None, Some(max)) => Some(Arc::new(Scheduler::new(max))),
+                (None, None) => None,
+            },
+            buffer_pool: self
+                .buffer_pool
+                .map(|buffers| Arc::new(BufferPool::new(buffers))),
+            corrupt_tile_retries: self.corrupt_tile_retries,
+            max_response_size: self.max_response_size,
+            read_timeout: self.read_timeout,
+            base_urls: Arc::new(self.base_urls),
+            dry_run: self.dry_run,
+        })
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::errors;
+
+/// The settings of a circuit breaker, which stops a Maptiler session from sending requests while
+/// the API appears to be down
+///
+/// The breaker watches the outcomes of the last `window` requests. Once that many have been made
+/// and at least `failure_rate` of them failed, with at least one failure, the breaker opens, and
+/// requests fail right away with [`errors::Error::CircuitOpen`] instead of being sent. After `cooldown`, a single probe
+/// request is let through. If it succeeds the breaker closes again, otherwise it stays open for
+/// another cooldown. Requests that were already in flight when the breaker opened don't close it.
+///
+/// A request fails if the server can't be reached, or if it answers with a 5xx status code. Other
+/// HTTP errors, such as 404 Not Found, mean that the API is up.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreaker {
+    failure_rate: f64,
+    window: usize,
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    /// Creates a new CircuitBreaker that opens once `failure_rate`, from 0.0 to 1.0, of the last
+    /// `window` requests failed, and probes the API again after `cooldown`
+    ///
+    /// A window of 0 is treated as 1, and `failure_rate` is clamped to the range 0.0 to 1.0, with
+    /// NaN treated as 1.0
+    ///
+    pub fn new(failure_rate: f64, window: usize, cooldown: Duration) -> Self {
+        let failure_rate = if failure_rate.is_nan() {
+            1.0
+        } else {
+            failure_rate.clamp(0.0, 1.0)
+        };
+
+        Self {
+            failure_rate,
+            window: window.max(1),
+            cooldown,
+        }
+    }
+
+    /// Returns the share of failed requests that opens the breaker
+    pub fn failure_rate(&self) -> f64 {
+        self.failure_rate
+    }
+
+    /// Returns how many of the latest requests the failure rate is measured over
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns how long the breaker stays open before a probe request is let through
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+}
+
+// Tracks the outcomes of requests against a CircuitBreaker, shared by every request of a session
+#[derive(Debug)]
+pub(crate) struct CircuitTracker {
+    breaker: CircuitBreaker,
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Debug)]
+enum CircuitState {
+    // Whether each of the latest requests failed, oldest first
+    Closed(VecDeque<bool>),
+    // No requests are sent until the given time
+    Open(Instant),
+    // A probe was sent at the given time, and other requests fail until it finishes
+    HalfOpen(Instant),
+}
+
+// A request let through by a CircuitTracker, which holds the time the probe was sent at if it is
+// one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Attempt(Option<Instant>);
+
+impl CircuitTracker {
+    pub(crate) fn new(breaker: CircuitBreaker) -> Self {
+        Self {
+            breaker,
+            state: Mutex::new(CircuitState::Closed(VecDeque::new())),
+        }
+    }
+
+    // Returns an error if the breaker is open, or lets the request through, possibly as a probe
+    pub(crate) fn check(&self) -> Result<Attempt, errors::Error> {
+        let mut state = self.lock();
+        let now = Instant::now();
+
+        match *state {
+            CircuitState::Closed(_) => Ok(Attempt::default()),
+            CircuitState::Open(until) if now >= until => {
+                *state = CircuitState::HalfOpen(now);
+                Ok(Attempt(Some(now)))
+            }
+            // A probe that never finished, because its future was dropped, is replaced by a new
+            // one after a cooldown
+            CircuitState::HalfOpen(probe) if now >= probe + self.breaker.cooldown => {
+                *state = CircuitState::HalfOpen(now);
+                Ok(Attempt(Some(now)))
+            }
+            CircuitState::Open(_) | CircuitState::HalfOpen(_) => Err(errors::Error::CircuitOpen),
+        }
+    }
+
+    // Records the outcome of a request that was let through by check
+    pub(crate) fn record(&self, attempt: Attempt, failed: bool) {
+        let mut state = self.lock();
+
+        match &mut *state {
+            CircuitState::Closed(outcomes) => {
+                outcomes.push_back(failed);
+
+                if outcomes.len() > self.breaker.window {
+                    outcomes.pop_front();
+                }
+
+                if outcomes.len() == self.breaker.window {
+                    let failures = outcomes.iter().filter(|failed| **failed).count();
+
+                    if failures > 0
+                        && failures as f64 >= self.breaker.failure_rate * outcomes.len() as f64
+                    {
+                        *state = CircuitState::Open(Instant::now() + self.breaker.cooldown);
+                    }
+                }
+            }
+            CircuitState::HalfOpen(probe) if attempt.0 == Some(*probe) => {
+                *state = if failed {
+                    CircuitState::Open(Instant::now() + self.breaker.cooldown)
+                } else {
+                    CircuitState::Closed(VecDeque::new())
+                };
+            }
+            // Only the outcome of the probe changes anything, requests that were already in flight
+            // when the breaker opened, or replaced probes, don't
+            CircuitState::HalfOpen(_) | CircuitState::Open(_) => {}
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CircuitState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
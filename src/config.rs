@@ -0,0 +1,239 @@
+//! Loading a Maptiler session configuration from a file
+//!
+//! Configuration files can be JSON, or TOML when the `toml` feature is enabled. Every field is
+//! optional. An example TOML configuration:
+//!
+//! ```toml
+//! # The environment variable that holds the API key, MAPTILER_KEY by default
+//! api_key_env = "MAPTILER_KEY"
+//! base_urls = ["https://tiles.internal.example.com", "https://api.maptiler.com"]
+//! max_bandwidth = 1000000
+//! max_response_size = 10000000
+//! max_concurrent_requests = 8
+//! corrupt_tile_retries = 2
+//! connect_timeout_ms = 5000
+//! read_timeout_ms = 10000
+//! default_tileset = "satellite"
+//!
+//! [quota]
+//! limit = 100000
+//! window_secs = 2592000
+//! wait = false
+//!
+//! [circuit_breaker]
+//! failure_rate = 0.5
+//! window = 20
+//! cooldown_secs = 30
+//! ```
+
+use std::{env, fmt, fs, path::Path, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    errors::ConfigError, AdaptiveConcurrency, CircuitBreaker, Maptiler, MaptilerBuilder, Quota,
+    TileSet,
+};
+
+const DEFAULT_API_KEY_ENV: &str = "MAPTILER_KEY";
+
+/// The configuration of a Maptiler session
+///
+/// Use [`Maptiler::from_config`] to create a session from it
+#[derive(Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The API key itself. Prefer [`Config::api_key_env`] so that keys stay out of files.
+    pub api_key: Option<String>,
+    /// The environment variable to read the API key from if [`Config::api_key`] isn't set,
+    /// MAPTILER_KEY by default
+    pub api_key_env: Option<String>,
+    /// The ordered list of base URLs, see [`MaptilerBuilder::base_urls`]
+    pub base_urls: Vec<String>,
+    /// The request budget, see [`MaptilerBuilder::quota`]
+    pub quota: Option<QuotaConfig>,
+    /// The bandwidth cap in bytes per second, see [`MaptilerBuilder::max_bandwidth`]
+    pub max_bandwidth: Option<u64>,
+    /// The cap on the size of a response body in bytes, see
+    /// [`MaptilerBuilder::max_response_size`]
+    pub max_response_size: Option<u64>,
+    /// See [`MaptilerBuilder::max_concurrent_requests`]
+    pub max_concurrent_requests: Option<usize>,
+    /// See [`MaptilerBuilder::adaptive_concurrency`]
+    pub adaptive_concurrency: Option<AdaptiveConcurrencyConfig>,
+    /// See [`MaptilerBuilder::circuit_breaker`]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// See [`MaptilerBuilder::corrupt_tile_retries`]
+    pub corrupt_tile_retries: u32,
+    /// The connect timeout in milliseconds, see [`MaptilerBuilder::connect_timeout`]
+    ///
+    /// This is ignored without the reqwest feature, since it only applies to the default client
+    ///
+    pub connect_timeout_ms: Option<u64>,
+    /// The read timeout in milliseconds, see [`MaptilerBuilder::read_timeout`]
+    pub read_timeout_ms: Option<u64>,
+    /// The endpoint of the tileset that applications should use by default, ex: "satellite"
+    pub default_tileset: Option<String>,
+}
+
+/// The request budget part of a [`Config`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuotaConfig {
+    /// The number of requests allowed per window
+    pub limit: u64,
+    /// The length of a window in seconds
+    pub window_secs: u64,
+    /// Whether requests over budget wait for the next window instead of failing
+    #[serde(default)]
+    pub wait: bool,
+}
+
+/// The adaptive concurrency part of a [`Config`], see [`AdaptiveConcurrency::new`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdaptiveConcurrencyConfig {
+    /// The lowest that the limit goes
+    pub min: usize,
+    /// The highest that the limit goes
+    pub max: usize,
+    /// How long a response may take in milliseconds before it counts as overload
+    pub target_latency_ms: u64,
+}
+
+/// The circuit breaker part of a [`Config`], see [`CircuitBreaker::new`]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    /// The share of failed requests, from 0.0 to 1.0, that opens the breaker
+    pub failure_rate: f64,
+    /// How many of the latest requests the failure rate is measured over
+    pub window: usize,
+    /// How long the breaker stays open in seconds before a probe request is let through
+    pub cooldown_secs: u64,
+}
+
+impl Config {
+    /// Loads a configuration file, choosing the format from its extension
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json_str(&contents),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml_str(&contents),
+            _ => Err(ConfigError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    /// Parses a JSON configuration
+    pub fn from_json_str(contents: &str) -> Result<Self, ConfigError> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    /// Parses a TOML configuration
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(contents: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Returns the API key, reading it from the environment if it isn't set directly
+    pub fn api_key(&self) -> Result<String, ConfigError> {
+        if let Some(api_key) = &self.api_key {
+            return Ok(api_key.clone());
+        }
+
+        let name = self.api_key_env.as_deref().unwrap_or(DEFAULT_API_KEY_ENV);
+
+        env::var(name).map_err(|_| ConfigError::MissingApiKey(name.to_string()))
+    }
+
+    /// Returns the default tileset, if one is configured
+    pub fn default_tileset(&self) -> Result<Option<TileSet>, ConfigError> {
+        match &self.default_tileset {
+            Some(endpoint) => TileSet::from_endpoint(endpoint)
+                .map(Some)
+                .ok_or_else(|| ConfigError::UnknownTileSet(endpoint.clone())),
+            None => Ok(None),
+        }
+    }
+
+    /// Creates a [`MaptilerBuilder`] with every setting of this configuration applied, so that
+    /// settings that can't be written in a file, like a custom client, can still be added
+    pub fn builder(&self) -> Result<MaptilerBuilder, ConfigError> {
+        let mut builder = Maptiler::builder(self.api_key()?)
+            .base_urls(self.base_urls.iter().cloned())
+            .corrupt_tile_retries(self.corrupt_tile_retries);
+
+        if let Some(quota) = self.quota {
+            let mut quota_setting = Quota::new(quota.limit, Duration::from_secs(quota.window_secs));
+
+            if quota.wait {
+                quota_setting = quota_setting.wait_for_window();
+            }
+
+            builder = builder.quota(quota_setting);
+        }
+
+        if let Some(max_bandwidth) = self.max_bandwidth {
+            builder = builder.max_bandwidth(max_bandwidth);
+        }
+
+        if let Some(max_response_size) = self.max_response_size {
+            builder = builder.max_response_size(max_response_size);
+        }
+
+        if let Some(max_concurrent_requests) = self.max_concurrent_requests {
+            builder = builder.max_concurrent_requests(max_concurrent_requests);
+        }
+
+        if let Some(adaptive) = self.adaptive_concurrency {
+            builder = builder.adaptive_concurrency(AdaptiveConcurrency::new(
+                adaptive.min,
+                adaptive.max,
+                Duration::from_millis(adaptive.target_latency_ms),
+            ));
+        }
+
+        if let Some(breaker) = self.circuit_breaker {
+            builder = builder.circuit_breaker(CircuitBreaker::new(
+                breaker.failure_rate,
+                breaker.window,
+                Duration::from_secs(breaker.cooldown_secs),
+            ));
+        }
+
+        #[cfg(feature = "reqwest")]
+        if let Some(timeout) = self.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(timeout));
+        }
+
+        if let Some(timeout) = self.read_timeout_ms {
+            builder = builder.read_timeout(Duration::from_millis(timeout));
+        }
+
+        Ok(builder)
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The API key is hidden so that it doesn't end up in logs
+        f.debug_struct("Config")
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("api_key_env", &self.api_key_env)
+            .field("base_urls", &self.base_urls)
+            .field("quota", &self.quota)
+            .field("max_bandwidth", &self.max_bandwidth)
+            .field("max_response_size", &self.max_response_size)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field("adaptive_concurrency", &self.adaptive_concurrency)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("corrupt_tile_retries", &self.corrupt_tile_retries)
+            .field("connect_timeout_ms", &self.connect_timeout_ms)
+            .field("read_timeout_ms", &self.read_timeout_ms)
+            .field("default_tileset", &self.default_tileset)
+            .finish()
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+/// A zoom level of the Tiled Web Map format
+///
+/// Taking a Zoom rather than a plain u32 lets the compiler catch a zoom level passed where a tile
+/// coordinate was expected, such as between [`TileRequest::new`](crate::TileRequest::new), which
+/// takes the zoom level last, and [`TileId::new`](crate::TileId::new), which takes it first.
+/// Plain u32s still convert into it implicitly.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Zoom(u32);
+
+impl Zoom {
+    /// Creates a new Zoom
+    pub const fn new(zoom: u32) -> Self {
+        Self(zoom)
+    }
+
+    /// Returns the zoom level as a u32
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// The x coordinate of a tile in the Tiled Web Map format, counting columns from the west
+///
+/// The x and y coordinates are different types, so swapping them where both are typed doesn't
+/// compile:
+///
+/// ```compile_fail
+/// use maptiler_cloud::{TileRequest, TileSet, TileX, TileY};
+///
+/// let (x, y) = (TileX::new(5), TileY::new(2));
+/// let tile_request = TileRequest::new(TileSet::Satellite, y, x, 3);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TileX(u32);
+
+impl TileX {
+    /// Creates a new TileX
+    pub const fn new(x: u32) -> Self {
+        Self(x)
+    }
+
+    /// Returns the coordinate as a u32
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// The y coordinate of a tile in the Tiled Web Map format, counting rows from the north, see
+/// [`TileX`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TileY(u32);
+
+impl TileY {
+    /// Creates a new TileY
+    pub const fn new(y: u32) -> Self {
+        Self(y)
+    }
+
+    /// Returns the coordinate as a u32
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+macro_rules! impl_conversions {
+    ($name:ident) => {
+        impl From<u32> for $name {
+            fn from(value: u32) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for u32 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+impl_conversions!(Zoom);
+impl_conversions!(TileX);
+impl_conversions!(TileY);
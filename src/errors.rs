@@ -1,13 +1,61 @@
-use crate::TileSet;
+use std::path::PathBuf;
+
+use crate::{backend::BackendError, TileSet};
 
 /// This error type wraps other crate's errors
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[cfg(feature = "reqwest")]
     #[error("Server request failed: {0}")]
     Reqwest(#[from] reqwest::Error),
 
+    // Only returned without the reqwest feature, but always declared so that enabling the
+    // feature doesn't break matches on it
+    #[error("No HTTP backend was given to the builder, and the reqwest feature is disabled")]
+    MissingBackend,
+
+    #[error("Server request failed: {0}")]
+    Backend(#[from] BackendError),
+
     #[error("Server returned HTTP error code: {0}")]
-    Http(reqwest::StatusCode),
+    Http(http::StatusCode),
+
+    #[error("Server returned invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid request argument: {0}")]
+    Argument(#[from] ArgumentError),
+
+    #[error("Request quota of {0} requests per window exceeded")]
+    QuotaExceeded(u64),
+
+    #[error("Circuit breaker is open after too many failed requests, the API appears to be down")]
+    CircuitOpen,
+
+    #[error("Response is larger than the limit of {0} bytes")]
+    ResponseTooLarge(u64),
+
+    #[error("Tile was truncated, expected {0} bytes but received {1}")]
+    TruncatedTile(u64, u64),
+
+    #[error("Tile is not a valid .{0} file")]
+    CorruptTile(&'static str),
+
+    #[error("Failed to write file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid configuration: {0}")]
+    Config(#[from] ConfigError),
+
+    #[error("Request was not sent, because the session is in dry-run mode")]
+    DryRunSession,
+}
+
+impl Error {
+    /// Returns true if this error means that a tile was downloaded, but it was truncated or corrupt
+    pub fn is_corrupt_tile(&self) -> bool {
+        matches!(self, Error::TruncatedTile(..) | Error::CorruptTile(_))
+    }
 }
 
 /// This error type represents an error from a request argument that was invalid
@@ -24,4 +72,36 @@ pub enum ArgumentError {
 
     #[error("Y coordinate {0} is too large for the zoom level {1} (max Y: {2})")]
     YTooLarge(u32, u32, u32),
+
+    #[error("Tile ({0}, {1}) at zoom level {2} is outside the bounds of the tileset {3}")]
+    OutsideBounds(u32, u32, u32, TileSet),
+
+    #[error("Custom tileset endpoint {0:?} or extension {1:?} can't be put in a URL")]
+    InvalidCustomTileSet(String, String),
+
+    #[error("Style id {0:?} may only contain ASCII letters, digits, '-', and '_'")]
+    InvalidStyleId(String),
+}
+
+/// This error type represents a configuration that couldn't be loaded
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read configuration file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid JSON configuration: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "toml")]
+    #[error("Invalid TOML configuration: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("Unsupported configuration file format: {}", .0.display())]
+    UnsupportedFormat(PathBuf),
+
+    #[error("No API key configured, and the environment variable {0} is not set")]
+    MissingApiKey(String),
+
+    #[error("Unknown tileset {0}")]
+    UnknownTileSet(String),
 }
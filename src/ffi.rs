@@ -0,0 +1,154 @@
+//! A C interface, built with the `ffi` feature
+//!
+//! The crate is built as a shared library, and `include/maptiler_cloud.h` declares these
+//! functions for C and C++. Requests block the calling thread.
+//!
+//! The crate is only a Rust library by default, so the shared library is built with:
+//!
+//! ```text
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! ```c
+//! MaptilerSession *session = maptiler_session_new("placeholder api key");
+//!
+//! uint8_t buffer[65536];
+//! size_t written;
+//! int status = maptiler_fetch_tile(session, "satellite", 2, 1, 1, buffer, sizeof buffer, &written);
+//!
+//! maptiler_session_free(session);
+//! ```
+
+use std::{
+    ffi::CStr,
+    os::raw::{c_char, c_int},
+    ptr, slice,
+};
+
+use crate::{Maptiler, TileRequest, TileSet};
+
+/// The tile was written to the buffer
+pub const MAPTILER_OK: c_int = 0;
+/// A pointer was null, a string wasn't UTF-8, the tileset is unknown, or the tile coordinates are
+/// invalid for it
+pub const MAPTILER_INVALID_ARGUMENT: c_int = -1;
+/// The request failed, ex: the server couldn't be reached, or returned an HTTP error
+pub const MAPTILER_REQUEST_FAILED: c_int = -2;
+/// The buffer is too small for the tile, and `written` holds the size that is needed
+pub const MAPTILER_BUFFER_TOO_SMALL: c_int = -3;
+
+/// A Maptiler Cloud session, see [`maptiler_session_new`]
+pub struct MaptilerSession {
+    maptiler: Maptiler,
+    runtime: tokio::runtime::Runtime,
+}
+
+/// Creates a new session with the given API key, returning null if it could not be created
+///
+/// # Safety
+///
+/// `api_key` must be null or a valid nul-terminated string
+///
+#[no_mangle]
+pub unsafe extern "C" fn maptiler_session_new(api_key: *const c_char) -> *mut MaptilerSession {
+    let api_key = match str_from_c(api_key) {
+        Some(api_key) => api_key,
+        None => return ptr::null_mut(),
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build();
+
+    match (Maptiler::new(api_key), runtime) {
+        (Ok(maptiler), Ok(runtime)) => {
+            Box::into_raw(Box::new(MaptilerSession { maptiler, runtime }))
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Frees a session created by [`maptiler_session_new`]
+///
+/// # Safety
+///
+/// `session` must be null or a session that hasn't been freed yet
+///
+#[no_mangle]
+pub unsafe extern "C" fn maptiler_session_free(session: *mut MaptilerSession) {
+    if !session.is_null() {
+        drop(Box::from_raw(session));
+    }
+}
+
+/// Downloads a tile of the tileset with the given endpoint, ex: "satellite", into the buffer
+///
+/// Returns one of the `MAPTILER_*` status codes. On success, `written` holds the size of the
+/// tile. If the buffer is too small, nothing is written to it, and `written` holds the size that
+/// is needed, so the call can be repeated with a larger buffer.
+///
+/// # Safety
+///
+/// `session` must be null or a live session, `endpoint` must be null or a valid nul-terminated
+/// string, `buffer` must be valid for writing `buffer_len` bytes, and `written` must be null or
+/// valid for writing
+///
+#[no_mangle]
+pub unsafe extern "C" fn maptiler_fetch_tile(
+    session: *const MaptilerSession,
+    endpoint: *const c_char,
+    zoom: u32,
+    x: u32,
+    y: u32,
+    buffer: *mut u8,
+    buffer_len: usize,
+    written: *mut usize,
+) -> c_int {
+    let session = match session.as_ref() {
+        Some(session) => session,
+        None => return MAPTILER_INVALID_ARGUMENT,
+    };
+
+    let set = match str_from_c(endpoint).and_then(TileSet::from_endpoint) {
+        Some(set) => set,
+        None => return MAPTILER_INVALID_ARGUMENT,
+    };
+
+    let tile_request = match TileRequest::new(set, x, y, zoom) {
+        Ok(tile_request) => tile_request,
+        Err(_) => return MAPTILER_INVALID_ARGUMENT,
+    };
+
+    let constructed = session.maptiler.create_request(tile_request);
+
+    let tile = match session.runtime.block_on(constructed.execute()) {
+        Ok(tile) => tile,
+        Err(_) => return MAPTILER_REQUEST_FAILED,
+    };
+
+    if !written.is_null() {
+        *written = tile.len();
+    }
+
+    if tile.len() > buffer_len {
+        return MAPTILER_BUFFER_TOO_SMALL;
+    }
+
+    if !tile.is_empty() {
+        if buffer.is_null() {
+            return MAPTILER_INVALID_ARGUMENT;
+        }
+
+        slice::from_raw_parts_mut(buffer, tile.len()).copy_from_slice(&tile);
+    }
+
+    MAPTILER_OK
+}
+
+unsafe fn str_from_c<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(string).to_str().ok()
+}
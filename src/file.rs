@@ -0,0 +1,55 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+// Distinguishes the temporary files of concurrent writes from the same process
+static NEXT_TEMPORARY: AtomicU64 = AtomicU64::new(0);
+
+// Writes a file so that it either has all of the given bytes or isn't changed at all
+//
+// The bytes are written to a temporary file next to it, which is renamed over it once it has been
+// flushed to disk. A crash or cancelled write leaves at most a stray temporary file behind, never
+// a truncated tile in its place.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let temporary = temporary_path(path)?;
+
+    let result = (|| {
+        let mut file = fs::File::create(&temporary)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&temporary, path)
+    })();
+
+    if result.is_err() {
+        // The original error is the one worth reporting
+        let _ = fs::remove_file(&temporary);
+    }
+
+    result
+}
+
+// A hidden file in the same directory, since renames are only atomic within a file system
+fn temporary_path(path: &Path) -> io::Result<PathBuf> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        )
+    })?;
+
+    let mut temporary_name = std::ffi::OsString::from(".");
+    temporary_name.push(file_name);
+    temporary_name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        NEXT_TEMPORARY.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    Ok(path.with_file_name(temporary_name))
+}
@@ -0,0 +1,43 @@
+//! Tiny representative tiles for testing without an API key or network
+//!
+//! There is one fixture per format that the built-in tilesets return. They are valid files, but
+//! hold next to no data: a single gray pixel, an empty vector layer, terrain whose elevation is
+//! the row of the pixel in meters, and a mesh with a single triangle.
+//!
+//! [`MockServer::serve_fixtures`](crate::mock::MockServer::serve_fixtures) answers every tile
+//! request with the fixture of its format.
+
+use crate::TileSet;
+
+/// A 1x1 gray JPEG, the format of the satellite tilesets
+pub const JPG: &[u8] = include_bytes!("../fixtures/satellite.jpg");
+
+/// A 256x256 TerrainRGB PNG, the format of the TerrainRGB and hillshading tilesets
+///
+/// The elevation of each pixel is its row in meters, so row 0 is at 0m and row 255 is at 255m
+pub const PNG: &[u8] = include_bytes!("../fixtures/terrain-rgb.png");
+
+/// A vector tile with a single empty "water" layer, the format of the vector tilesets
+pub const PBF: &[u8] = include_bytes!("../fixtures/vector.pbf");
+
+/// A quantized mesh with a single triangle, the format of the Terrain3D tileset
+pub const QUANTIZED_MESH: &[u8] = include_bytes!("../fixtures/terrain.quantized-mesh");
+
+/// Returns the fixture for the given file extension, ex: "jpg"
+pub fn for_extension(extension: &str) -> Option<&'static [u8]> {
+    match extension {
+        "jpg" => Some(JPG),
+        "png" => Some(PNG),
+        "pbf" => Some(PBF),
+        "quantized-mesh-1.0" => Some(QUANTIZED_MESH),
+        _ => None,
+    }
+}
+
+/// Returns the fixture in the format of the given tileset
+///
+/// Custom tilesets only have a fixture if their extension is one of the built-in formats
+///
+pub fn for_tileset(set: TileSet) -> Option<&'static [u8]> {
+    for_extension(set.file_extension())
+}
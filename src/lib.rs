@@ -1,4 +1,11 @@
-use std::{fmt::Display, sync::Arc};
+use std::{fmt::Display, sync::Arc, task::Poll, time::Duration};
+
+use backend::{BackendError, BackendErrorKind, HttpBackend, HttpRequest, HttpResponse};
+use circuit::CircuitTracker;
+use pool::BufferPool;
+use quota::QuotaTracker;
+use scheduler::Scheduler;
+use throttle::Throttle;
 
 /// Rust bindings for the [Maptiler Cloud API](https://cloud.maptiler.com/maps/)
 ///
@@ -40,7 +47,60 @@ use std::{fmt::Display, sync::Arc};
 /// From there, most users will write those bytes to a file, or load them into another function
 /// that will be able to display the image from the raw JPEG bytes.
 ///
+pub mod backend;
+mod builder;
+mod circuit;
+pub mod config;
+mod coords;
 pub mod errors;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod file;
+#[cfg(feature = "test-util")]
+pub mod fixtures;
+#[cfg(feature = "test-util")]
+pub mod mock;
+#[cfg(feature = "poll")]
+pub mod poll;
+mod pool;
+pub mod projection;
+#[cfg(feature = "python")]
+mod python;
+mod queue;
+mod quota;
+mod scheduler;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod source;
+pub mod style;
+#[cfg(feature = "otel")]
+mod telemetry;
+pub mod terrain;
+mod throttle;
+mod tile;
+mod tile_id;
+pub mod tilejson;
+#[cfg(feature = "tower")]
+mod tower;
+mod verify;
+pub mod viewer;
+pub mod viewport;
+
+pub use builder::MaptilerBuilder;
+pub use circuit::CircuitBreaker;
+pub use config::Config;
+pub use coords::{TileX, TileY, Zoom};
+pub use queue::{DownloadQueue, QueuedDownload};
+pub use quota::{Quota, QuotaUsage};
+pub use scheduler::{AdaptiveConcurrency, Priority};
+pub use source::{MaptilerTileSource, TileSource};
+pub use tile::{HillshadeTile, QuantizedMeshTile, SatelliteTile, TerrainRgbTile, Tile, VectorTile};
+pub use tile_id::TileId;
+pub use tilejson::TileJson;
+
+// The most that is allocated for a response body before any of it has arrived, since the
+// Content-Length header can't be trusted to be reasonable
+const MAX_PREALLOCATION: u64 = 64 * 1024;
 
 /// The different types of tilesets that Maptiler Cloud supports
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -101,6 +161,25 @@ pub enum TileSet {
 }
 
 impl TileSet {
+    /// Every built-in tileset, which is every variant except [`TileSet::Custom`]
+    pub const BUILT_IN: [TileSet; 15] = [
+        TileSet::Contours,
+        TileSet::Countries,
+        TileSet::Hillshading,
+        TileSet::Land,
+        TileSet::Landcover,
+        TileSet::MaptilerPlanet,
+        TileSet::MaptilerPlanetLite,
+        TileSet::OpenMapTiles,
+        TileSet::OpenMapTilesWGS84,
+        TileSet::Outdoor,
+        TileSet::Satellite,
+        TileSet::SatelliteMediumRes2016,
+        TileSet::SatelliteMediumRes2018,
+        TileSet::Terrain3D,
+        TileSet::TerrainRGB,
+    ];
+
     /// Returns the endpoint that this tileset requires on the API request
     ///
     /// For the satellite data tileset, the endpoint would be "satellite"
@@ -128,6 +207,34 @@ impl TileSet {
         }
     }
 
+    /// Returns the built-in tileset that uses the given API endpoint, if there is one
+    ///
+    /// This is the inverse of [`TileSet::endpoint`], so "satellite" would return
+    /// [`TileSet::Satellite`]. Custom tilesets are never returned.
+    ///
+    pub fn from_endpoint(endpoint: &str) -> Option<Self> {
+        let set = match endpoint {
+            "contours" => TileSet::Contours,
+            "countries" => TileSet::Countries,
+            "hillshades" => TileSet::Hillshading,
+            "land" => TileSet::Land,
+            "landcover" => TileSet::Landcover,
+            "v3" => TileSet::MaptilerPlanet,
+            "v3-lite" => TileSet::MaptilerPlanetLite,
+            "v3-openmaptiles" => TileSet::OpenMapTiles,
+            "v3-4326" => TileSet::OpenMapTilesWGS84,
+            "outdoor" => TileSet::Outdoor,
+            "satellite" => TileSet::Satellite,
+            "satellite-mediumres" => TileSet::SatelliteMediumRes2016,
+            "satellite-mediumres-2018" => TileSet::SatelliteMediumRes2018,
+            "terrain-quantized-mesh" => TileSet::Terrain3D,
+            "terrain-rgb" => TileSet::TerrainRGB,
+            _ => return None,
+        };
+
+        Some(set)
+    }
+
     /// Returns the maximum zoom level that this tileset supports
     ///
     /// The custom tileset variant has a maximum of 20 here, but it may be lower than that. Take
@@ -215,6 +322,40 @@ impl TileSet {
     }
 }
 
+impl TileSet {
+    // Checks that the endpoint and extension of a custom tileset can be put in a URL
+    //
+    // Characters like '/', '?', and '#' would change which path is requested, or add query
+    // parameters to it, and URLs are percent-encoded anyway, so a '%' can only be a mistake
+    fn check_custom(&self) -> Result<(), errors::ArgumentError> {
+        let (endpoint, extension) = match self {
+            TileSet::Custom {
+                endpoint,
+                extension,
+            } => (endpoint, extension),
+            _ => return Ok(()),
+        };
+
+        let valid = |segment: &str| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && !segment.chars().any(|c| {
+                    matches!(c, '/' | '\\' | '?' | '#' | '%') || c.is_whitespace() || c.is_control()
+                })
+        };
+
+        if valid(endpoint) && valid(extension) {
+            Ok(())
+        } else {
+            Err(errors::ArgumentError::InvalidCustomTileSet(
+                endpoint.to_string(),
+                extension.to_string(),
+            ))
+        }
+    }
+}
+
 impl Display for TileSet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -245,13 +386,57 @@ impl Display for TileSet {
     }
 }
 
+/// The id of a Maptiler Cloud map style, which has been checked to be safe to put in a URL
+///
+/// This is either the id of a built-in map, such as "streets-v2" or "satellite", or the UUID of a
+/// custom map from a Maptiler Cloud account
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StyleId(String);
+
+impl StyleId {
+    /// Creates a new StyleId, checking that it only contains ASCII letters, digits, '-', and '_'
+    ///
+    /// Anything else could break the path of the URL that it is put in, or add query parameters
+    /// to it, so it is rejected
+    ///
+    pub fn new<S>(style_id: S) -> Result<Self, errors::ArgumentError>
+    where
+        S: Into<String>,
+    {
+        let style_id = style_id.into();
+
+        let valid = !style_id.is_empty()
+            && style_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if valid {
+            Ok(Self(style_id))
+        } else {
+            Err(errors::ArgumentError::InvalidStyleId(style_id))
+        }
+    }
+
+    /// Returns the style id as a &str
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for StyleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// A struct containing the arguments required to make a request for a tile
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct TileRequest {
     set: TileSet,
-    zoom: u32,
-    tile_x: u32,
-    tile_y: u32,
+    zoom: Zoom,
+    x: TileX,
+    y: TileY,
 }
 
 impl TileRequest {
@@ -265,20 +450,65 @@ impl TileRequest {
     ///
     /// The x and y positions must be in bounds
     ///
-    pub fn new(set: TileSet, x: u32, y: u32, zoom: u32) -> Result<Self, errors::ArgumentError> {
+    /// The coordinates and zoom level are either plain u32s or a [`TileX`], [`TileY`], and
+    /// [`Zoom`]
+    ///
+    pub fn new(
+        set: TileSet,
+        x: impl Into<TileX>,
+        y: impl Into<TileY>,
+        zoom: impl Into<Zoom>,
+    ) -> Result<Self, errors::ArgumentError> {
+        let (x, y, zoom) = (x.into().get(), y.into().get(), zoom.into().get());
+
+        Self::new_with_zoom_range(set, x, y, zoom, set.min_zoom(), set.max_zoom())
+    }
+
+    /// Creates a new TileRequest, validated against the live TileJSON metadata of the tileset
+    ///
+    /// This works the same as [`TileRequest::new`], except that the zoom range comes from the
+    /// TileJSON instead of the hardcoded tables in [`TileSet`], and the tile must overlap the
+    /// bounds that the tileset reports covering. This is the only way to get real limits for a
+    /// [`TileSet::Custom`] tileset. Zoom levels above [`TileId::MAX_ZOOM`] are always rejected.
+    ///
+    /// The TileJSON can be requested using [`Maptiler::fetch_tilejson`]
+    ///
+    pub fn new_with_tilejson(
+        set: TileSet,
+        x: impl Into<TileX>,
+        y: impl Into<TileY>,
+        zoom: impl Into<Zoom>,
+        tilejson: &TileJson,
+    ) -> Result<Self, errors::ArgumentError> {
+        let (x, y, zoom) = (x.into().get(), y.into().get(), zoom.into().get());
+        let request =
+            Self::new_with_zoom_range(set, x, y, zoom, tilejson.minzoom, tilejson.maxzoom)?;
+
+        if !tilejson.covers(x, y, zoom) {
+            return Err(errors::ArgumentError::OutsideBounds(x, y, zoom, set));
+        }
+
+        Ok(request)
+    }
+
+    fn new_with_zoom_range(
+        set: TileSet,
+        x: u32,
+        y: u32,
+        zoom: u32,
+        min_zoom: u32,
+        max_zoom: u32,
+    ) -> Result<Self, errors::ArgumentError> {
+        set.check_custom()?;
+
+        // A zoom range from TileJSON may be anything, but tile coordinates only go so deep
+        let max_zoom = max_zoom.min(TileId::MAX_ZOOM);
+
         // Check if the zoom is valid
-        if zoom > set.max_zoom() {
-            return Err(errors::ArgumentError::ZoomTooLarge(
-                zoom,
-                set,
-                set.max_zoom(),
-            ));
-        } else if zoom < set.min_zoom() {
-            return Err(errors::ArgumentError::ZoomTooSmall(
-                zoom,
-                set,
-                set.min_zoom(),
-            ));
+        if zoom > max_zoom {
+            return Err(errors::ArgumentError::ZoomTooLarge(zoom, set, max_zoom));
+        } else if zoom < min_zoom {
+            return Err(errors::ArgumentError::ZoomTooSmall(zoom, set, min_zoom));
         }
 
         // Check if the coordinates are valid
@@ -294,9 +524,9 @@ impl TileRequest {
 
         Ok(Self {
             set,
-            zoom,
-            tile_x: x,
-            tile_y: y,
+            zoom: Zoom::new(zoom),
+            x: TileX::new(x),
+            y: TileY::new(y),
         })
     }
 
@@ -321,17 +551,17 @@ impl TileRequest {
 
     /// Returns the x coordinate of this tile request
     pub fn x(&self) -> u32 {
-        self.tile_x
+        self.x.get()
     }
 
     /// Returns the y coordinate of this tile request
     pub fn y(&self) -> u32 {
-        self.tile_y
+        self.y.get()
     }
 
     /// Returns the zoom level of this tile request
     pub fn zoom(&self) -> u32 {
-        self.zoom
+        self.zoom.get()
     }
 }
 
@@ -341,58 +571,666 @@ impl From<TileRequest> for RequestType {
     }
 }
 
+/// A struct containing the arguments required to request the TileJSON metadata of a tileset
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TileJsonRequest {
+    set: TileSet,
+}
+
+impl TileJsonRequest {
+    /// Creates a new TileJsonRequest for the given tileset
+    pub fn new(set: TileSet) -> Self {
+        Self { set }
+    }
+
+    /// Returns the tileset that this request is for
+    pub fn set(&self) -> TileSet {
+        self.set
+    }
+}
+
+impl From<TileJsonRequest> for RequestType {
+    fn from(tilejson_request: TileJsonRequest) -> Self {
+        RequestType::TileJsonRequest(tilejson_request)
+    }
+}
+
+/// A struct containing the arguments required to request the style JSON of a map style
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct StyleRequest {
+    style_id: StyleId,
+}
+
+impl StyleRequest {
+    /// Creates a new StyleRequest for the given map style
+    pub fn new(style_id: StyleId) -> Self {
+        Self { style_id }
+    }
+
+    /// Returns the map style that this request is for
+    pub fn style_id(&self) -> &StyleId {
+        &self.style_id
+    }
+}
+
+impl From<StyleRequest> for RequestType {
+    fn from(style_request: StyleRequest) -> Self {
+        RequestType::StyleRequest(style_request)
+    }
+}
+
 /// The type of request to the Maptiler Cloud API
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum RequestType {
     TileRequest(TileRequest),
+    TileJsonRequest(TileJsonRequest),
+    StyleRequest(StyleRequest),
+}
+
+impl RequestType {
+    // Builds the path of this request relative to the base URL, without the API key
+    pub(crate) fn path(&self) -> String {
+        match self {
+            RequestType::TileRequest(tile_request) => {
+                let tileset = &tile_request.set;
+                let endpoint = encode_path_segment(tileset.endpoint());
+                let extension = encode_path_segment(tileset.file_extension());
+                let zoom = tile_request.zoom;
+                let x = tile_request.x;
+                let y = tile_request.y;
+
+                // https://api.maptiler.com/tiles/satellite/{z}/{x}/{y}.jpg?key=AAAAAAAAAAAAAAAAAA
+                format!("/tiles/{}/{}/{}/{}.{}", endpoint, zoom, x, y, extension)
+            }
+            RequestType::TileJsonRequest(tilejson_request) => {
+                // https://api.maptiler.com/tiles/satellite/tiles.json?key=AAAAAAAAAAAAAAAAAA
+                format!(
+                    "/tiles/{}/tiles.json",
+                    encode_path_segment(tilejson_request.set.endpoint())
+                )
+            }
+            RequestType::StyleRequest(style_request) => {
+                // https://api.maptiler.com/maps/streets-v2/style.json?key=AAAAAAAAAAAAAAAAAA
+                format!("/maps/{}/style.json", style_request.style_id)
+            }
+        }
+    }
 }
 
 /// Represents a request that has already been constructed using the Maptiler that created it. This
 /// can be directly await-ed using execute()
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ConstructedRequest {
     api_key: Arc<String>,
     inner: RequestType,
-    client: Arc<reqwest::Client>,
+    backend: Arc<dyn HttpBackend>,
+    quota: Option<Arc<QuotaTracker>>,
+    throttle: Option<Arc<Throttle>>,
+    circuit: Option<Arc<CircuitTracker>>,
+    scheduler: Option<Arc<Scheduler>>,
+    buffer_pool: Option<Arc<BufferPool>>,
+    priority: Priority,
+    fallbacks: Vec<TileSet>,
+    corrupt_tile_retries: u32,
+    max_response_size: Option<u64>,
+    read_timeout: Option<Duration>,
+    base_urls: Arc<Vec<String>>,
+    dry_run: bool,
+}
+
+impl std::fmt::Debug for ConstructedRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The API key is left out so that it doesn't end up in logs
+        f.debug_struct("ConstructedRequest")
+            .field("inner", &self.inner)
+            .field("priority", &self.priority)
+            .field("fallbacks", &self.fallbacks)
+            .field("base_urls", &self.base_urls)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A request that would have been sent, as returned by [`ConstructedRequest::dry_run`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRun {
+    /// The method of the request
+    pub method: http::Method,
+    /// The full URL of the request, including the API key
+    pub url: String,
+    /// The headers that would be sent with the request
+    pub headers: http::HeaderMap,
+}
+
+/// The outcome of [`ConstructedRequest::execute_or_dry_run`] and
+/// [`ConstructedRequest::head_or_dry_run`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Execution<T> {
+    /// The request was sent, and this is its response
+    Sent(T),
+    /// The session is in dry-run mode, and this is the request that would have been sent
+    DryRun(DryRun),
+}
+
+/// The response headers of a HEAD request, which describe a tile without downloading it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadResponse {
+    /// The HTTP status code that the server returned
+    pub status: http::StatusCode,
+    /// The size of the body in bytes, if the server reported it
+    pub content_length: Option<u64>,
+    /// The ETag of the body, if the server reported it
+    pub etag: Option<String>,
+}
+
+impl HeadResponse {
+    /// Returns true if the requested resource exists and is accessible with this API key
+    pub fn exists(&self) -> bool {
+        self.status == http::StatusCode::OK
+    }
 }
 
 impl ConstructedRequest {
+    /// Sets the priority of this request, which is [`Priority::Normal`] by default
+    ///
+    /// When the session is already running as many requests as it allows, see
+    /// [`MaptilerBuilder::max_concurrent_requests`], higher priority requests are started first.
+    /// This lets tiles in the visible viewport jump ahead of background prefetches.
+    ///
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the priority of this request
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Sets the tilesets to try in order if the tile can't be downloaded from its own tileset,
+    /// ex: [`TileSet::SatelliteMediumRes2018`] for a [`TileSet::Satellite`] tile
+    ///
+    /// A fallback is tried after an HTTP error, such as 404 Not Found where the tileset has no
+    /// coverage, a corrupt tile, or a failure to reach the server. Fallbacks that don't have the
+    /// tile's zoom level are skipped. If every tileset fails, the error of the first one is
+    /// returned. Use [`ConstructedRequest::execute_with_source`] to find out which tileset the
+    /// tile came from.
+    ///
+    /// This only applies to tile requests
+    ///
+    pub fn with_fallbacks<I>(mut self, fallbacks: I) -> Self
+    where
+        I: IntoIterator<Item = TileSet>,
+    {
+        self.fallbacks = fallbacks.into_iter().collect();
+        self
+    }
+
     /// Actually performs the API call to the Maptiler Cloud API
+    ///
+    /// Tiles are checked to be complete and of the right format for their tileset, and corrupt
+    /// ones are downloaded again as many times as the session allows, see
+    /// [`MaptilerBuilder::corrupt_tile_retries`]
+    ///
     pub async fn execute(&self) -> Result<Vec<u8>, errors::Error> {
-        match self.inner {
-            RequestType::TileRequest(tile_request) => self.execute_tile(tile_request).await,
+        self.execute_with_source()
+            .await
+            .map(|(_source, bytes)| bytes)
+    }
+
+    /// Performs the API call like [`ConstructedRequest::execute`], unless the session is in
+    /// dry-run mode, where the request that would have been sent is returned instead
+    ///
+    /// See [`MaptilerBuilder::dry_run`]
+    ///
+    pub async fn execute_or_dry_run(&self) -> Result<Execution<Vec<u8>>, errors::Error> {
+        if self.dry_run {
+            return Ok(Execution::DryRun(self.dry_run()));
+        }
+
+        self.execute().await.map(Execution::Sent)
+    }
+
+    /// Performs the API call like [`ConstructedRequest::execute`], and also returns the request
+    /// that the response came from
+    ///
+    /// This is the request itself, unless it fell back to another tileset, see
+    /// [`ConstructedRequest::with_fallbacks`]
+    ///
+    pub async fn execute_with_source(&self) -> Result<(RequestType, Vec<u8>), errors::Error> {
+        let first_error = match self.execute_single().await {
+            Ok(bytes) => return Ok((self.inner.clone(), bytes)),
+            Err(e) => e,
+        };
+
+        let tile_request = match &self.inner {
+            RequestType::TileRequest(tile_request) if !self.fallbacks.is_empty() => *tile_request,
+            _ => return Err(first_error),
+        };
+
+        // Errors about the request itself, or the session, would be the same for every tileset
+        if !matches!(
+            first_error,
+            errors::Error::Http(_)
+                | errors::Error::Backend(_)
+                | errors::Error::TruncatedTile(..)
+                | errors::Error::CorruptTile(_)
+        ) {
+            return Err(first_error);
+        }
+
+        for &set in &self.fallbacks {
+            let fallback = match TileRequest::new(
+                set,
+                tile_request.x(),
+                tile_request.y(),
+                tile_request.zoom(),
+            ) {
+                Ok(fallback) => RequestType::TileRequest(fallback),
+                Err(_) => continue,
+            };
+
+            let constructed = ConstructedRequest {
+                inner: fallback.clone(),
+                fallbacks: Vec::new(),
+                ..self.clone()
+            };
+
+            if let Ok(bytes) = constructed.execute_single().await {
+                return Ok((fallback, bytes));
+            }
+        }
+
+        Err(first_error)
+    }
+
+    // Performs the API call without falling back to other tilesets
+    async fn execute_single(&self) -> Result<Vec<u8>, errors::Error> {
+        if self.dry_run {
+            return Err(errors::Error::DryRunSession);
+        }
+
+        let set = match &self.inner {
+            RequestType::TileRequest(tile_request) => Some(tile_request.set),
+            RequestType::TileJsonRequest(_) | RequestType::StyleRequest(_) => None,
+        };
+
+        let mut retries = 0;
+
+        loop {
+            let result = self
+                .execute_once()
+                .await
+                .and_then(|(body, content_length)| {
+                    if let Some(set) = set {
+                        verify::verify_tile(set, &body, content_length)?;
+                    }
+
+                    Ok(body)
+                });
+
+            match result {
+                Err(e) if e.is_corrupt_tile() && retries < self.corrupt_tile_retries => {
+                    retries += 1;
+                }
+                result => return result,
+            }
         }
     }
 
-    async fn execute_tile(&self, tile_request: TileRequest) -> Result<Vec<u8>, errors::Error> {
-        let tileset = &tile_request.set;
-        let endpoint = tileset.endpoint();
-        let extension = tileset.file_extension();
-        let zoom = tile_request.zoom;
-        let x = tile_request.tile_x;
-        let y = tile_request.tile_y;
+    /// Performs the API call like [`ConstructedRequest::execute`], and saves the response to a file
+    ///
+    /// The file is replaced atomically: the response is written to a temporary file next to it,
+    /// which is renamed over it once complete. If the request fails, or the program crashes or is
+    /// cancelled part way through, the file is left as it was instead of being truncated.
+    ///
+    pub async fn execute_to_file<P>(&self, path: P) -> Result<(), errors::Error>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let bytes = self.execute().await?;
+
+        file::write_atomic(path.as_ref(), &bytes)?;
+
+        // The tile is on disk, so its buffer can go to the next request
+        if let Some(pool) = &self.buffer_pool {
+            pool.put(bytes);
+        }
+
+        Ok(())
+    }
 
-        // https://api.maptiler.com/tiles/satellite/{z}/{x}/{y}.jpg?key=AAAAAAAAAAAAAAAAAA
-        let url = format!(
-            "https://api.maptiler.com/tiles/{}/{}/{}/{}.{}?key={}",
-            endpoint, zoom, x, y, extension, &self.api_key
-        );
+    // Downloads the body once, along with the length that the server reported for it
+    async fn execute_once(&self) -> Result<(Vec<u8>, Option<u64>), errors::Error> {
+        // The slot is held until the whole body has been read
+        let _permit = self.acquire_slot().await;
+
+        // An open circuit breaker fails before the quota is used up
+        let attempt = self.check_circuit()?;
+
+        if let Some(quota) = &self.quota {
+            quota.acquire(&*self.backend).await?;
+        }
 
         // Perform the actual request
-        let res = self.client.get(url).send().await?;
+        let res = self.send(http::Method::GET, attempt).await?;
 
-        match res.status() {
-            reqwest::StatusCode::OK => Ok(res.bytes().await?.to_vec()),
+        match res.status {
+            http::StatusCode::OK => {
+                let content_length = decoded_length(&res.headers);
+                Ok((self.read_body(res).await?, content_length))
+            }
             status => Err(errors::Error::Http(status)),
         }
     }
+
+    // Reads the whole body of a response, respecting the bandwidth limit of the session
+    async fn read_body(&self, mut res: HttpResponse) -> Result<Vec<u8>, errors::Error> {
+        let content_length = content_length(&res.headers);
+        let limit = self.max_response_size.unwrap_or(u64::MAX);
+
+        // Checked before anything is allocated for the body
+        if matches!(content_length, Some(length) if length > limit) {
+            return Err(errors::Error::ResponseTooLarge(limit));
+        }
+
+        // Bodies larger than this grow the buffer as they arrive
+        let capacity = content_length.unwrap_or(0).min(MAX_PREALLOCATION) as usize;
+        let mut body = match &self.buffer_pool {
+            Some(pool) => pool.take(capacity),
+            None => Vec::with_capacity(capacity),
+        };
+
+        while let Some(chunk) = self.read_chunk(&mut *res.body).await? {
+            // The server may send more than it reported, or not report a length at all
+            if (body.len() + chunk.len()) as u64 > limit {
+                return Err(errors::Error::ResponseTooLarge(limit));
+            }
+
+            if let Some(throttle) = &self.throttle {
+                throttle.consume(&*self.backend, chunk.len()).await;
+            }
+
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+
+    // Reads the next chunk of a body, giving up if it takes longer than the read timeout
+    async fn read_chunk(
+        &self,
+        body: &mut dyn backend::ResponseBody,
+    ) -> Result<Option<bytes::Bytes>, BackendError> {
+        let timeout = match self.read_timeout {
+            Some(timeout) => timeout,
+            None => return body.chunk().await,
+        };
+
+        let mut chunk = body.chunk();
+        let mut sleep = self.backend.sleep(timeout);
+
+        // Whichever finishes first wins, without needing a particular async runtime
+        std::future::poll_fn(|cx| {
+            if let Poll::Ready(chunk) = chunk.as_mut().poll(cx) {
+                return Poll::Ready(chunk);
+            }
+
+            sleep.as_mut().poll(cx).map(|()| {
+                Err(BackendError::new(
+                    BackendErrorKind::Timeout,
+                    "no response body was received within the read timeout",
+                ))
+            })
+        })
+        .await
+    }
+
+    /// Performs a HEAD request to the Maptiler Cloud API, without downloading the body
+    ///
+    /// This is a cheap way to check if a tile is available, and how large it is, before deciding
+    /// to download it. Unlike [`ConstructedRequest::execute`], an HTTP error status is not an
+    /// error here, and is returned in [`HeadResponse::status`] instead.
+    ///
+    /// HEAD requests still count against the [`Quota`] of the session, since Maptiler Cloud
+    /// counts every request to the API.
+    ///
+    pub async fn head(&self) -> Result<HeadResponse, errors::Error> {
+        if self.dry_run {
+            return Err(errors::Error::DryRunSession);
+        }
+
+        let _permit = self.acquire_slot().await;
+        let attempt = self.check_circuit()?;
+
+        // Maptiler Cloud counts HEAD requests like any other
+        if let Some(quota) = &self.quota {
+            quota.acquire(&*self.backend).await?;
+        }
+
+        let res = self.send(http::Method::HEAD, attempt).await?;
+
+        let etag = res
+            .headers
+            .get(http::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        Ok(HeadResponse {
+            status: res.status,
+            content_length: content_length(&res.headers),
+            etag,
+        })
+    }
+
+    /// Performs a HEAD request like [`ConstructedRequest::head`], unless the session is in dry-run
+    /// mode, where the request that would have been sent is returned instead
+    ///
+    /// See [`MaptilerBuilder::dry_run`]
+    ///
+    pub async fn head_or_dry_run(&self) -> Result<Execution<HeadResponse>, errors::Error> {
+        if self.dry_run {
+            return Ok(Execution::DryRun(
+                self.dry_run_with_method(http::Method::HEAD),
+            ));
+        }
+
+        self.head().await.map(Execution::Sent)
+    }
+
+    // Waits for the session to have room for another request, if it limits how many may run at once
+    async fn acquire_slot(&self) -> Option<scheduler::Permit> {
+        match &self.scheduler {
+            Some(scheduler) => Some(scheduler.acquire(self.priority).await),
+            None => None,
+        }
+    }
+
+    fn check_circuit(&self) -> Result<circuit::Attempt, errors::Error> {
+        match &self.circuit {
+            Some(circuit) => circuit.check(),
+            None => Ok(circuit::Attempt::default()),
+        }
+    }
+
+    // Sends the request to each base URL of the session in order, until one of them can be
+    // connected to
+    async fn send(
+        &self,
+        method: http::Method,
+        attempt: circuit::Attempt,
+    ) -> Result<HttpResponse, errors::Error> {
+        let mut base_urls = self.base_urls.iter().peekable();
+
+        while let Some(base_url) = base_urls.next() {
+            let request = self.http_request(method.clone(), base_url);
+
+            #[cfg(feature = "otel")]
+            let (request, telemetry) = telemetry::RequestTelemetry::start(request);
+
+            let started = std::time::Instant::now();
+            let result = self.backend.send(request).await;
+
+            #[cfg(feature = "otel")]
+            telemetry.finish(&result);
+
+            match result {
+                // Only fail over if the server couldn't be reached, an HTTP error status would be
+                // the same from every mirror
+                Err(e) if (e.is_connect() || e.is_timeout()) && base_urls.peek().is_some() => {}
+                result => {
+                    if let Some(circuit) = &self.circuit {
+                        circuit.record(
+                            attempt,
+                            match &result {
+                                Ok(res) => res.status.is_server_error(),
+                                Err(_) => true,
+                            },
+                        );
+                    }
+
+                    if let Some(scheduler) = &self.scheduler {
+                        let overloaded = match &result {
+                            Ok(res) => {
+                                res.status == http::StatusCode::TOO_MANY_REQUESTS
+                                    || res.status.is_server_error()
+                            }
+                            Err(_) => true,
+                        };
+
+                        scheduler.record(started.elapsed(), overloaded);
+                    }
+
+                    return Ok(result?);
+                }
+            }
+        }
+
+        // The builder never creates a session without a base URL
+        unreachable!("session has no base URLs")
+    }
+
+    /// Returns the request that [`ConstructedRequest::execute`] would send, without sending it
+    ///
+    /// This is useful for debugging and auditing, or for handing a list of requests to an external
+    /// download tool. The URL uses the first base URL of the session, and includes the API key.
+    ///
+    pub fn dry_run(&self) -> DryRun {
+        self.dry_run_with_method(http::Method::GET)
+    }
+
+    /// Checks a response to this request that was received by other means, the same way that
+    /// [`ConstructedRequest::execute`] does, returning the body if it is valid
+    ///
+    /// Together with [`ConstructedRequest::dry_run`], this lets the crate be used without doing
+    /// any I/O itself, ex: with the default `reqwest` feature disabled. The response must have a
+    /// 200 OK status, fit in the maximum response size of the session, and for tiles, be complete
+    /// and of the right format for the tileset.
+    ///
+    pub fn read_response(
+        &self,
+        status: http::StatusCode,
+        headers: &http::HeaderMap,
+        body: Vec<u8>,
+    ) -> Result<Vec<u8>, errors::Error> {
+        if status != http::StatusCode::OK {
+            return Err(errors::Error::Http(status));
+        }
+
+        if let Some(limit) = self.max_response_size {
+            if body.len() as u64 > limit {
+                return Err(errors::Error::ResponseTooLarge(limit));
+            }
+        }
+
+        if let RequestType::TileRequest(tile_request) = &self.inner {
+            verify::verify_tile(tile_request.set, &body, decoded_length(headers))?;
+        }
+
+        Ok(body)
+    }
+
+    fn dry_run_with_method(&self, method: http::Method) -> DryRun {
+        let request = self.http_request(method, &self.base_urls[0]);
+
+        DryRun {
+            method: request.method,
+            url: request.url,
+            headers: request.headers,
+        }
+    }
+
+    fn http_request(&self, method: http::Method, base_url: &str) -> HttpRequest {
+        HttpRequest {
+            method,
+            url: self.url(base_url),
+            headers: http::HeaderMap::new(),
+        }
+    }
+
+    // Builds the full URL of this request, including the API key
+    fn url(&self, base_url: &str) -> String {
+        format!("{}{}?key={}", base_url, self.inner.path(), &self.api_key)
+    }
+}
+
+// Percent-encodes everything but the unreserved characters of RFC 3986, so that a string can't
+// break out of the path segment that it is put in
+fn encode_path_segment(segment: &str) -> std::borrow::Cow<'_, str> {
+    let unreserved = |b: u8| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~');
+
+    if segment.bytes().all(unreserved) {
+        return std::borrow::Cow::Borrowed(segment);
+    }
+
+    let mut encoded = String::with_capacity(segment.len() * 3);
+
+    for b in segment.bytes() {
+        if unreserved(b) {
+            encoded.push(char::from(b));
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+
+    std::borrow::Cow::Owned(encoded)
+}
+
+// Reads the Content-Length header of a response
+//
+// The body of a HEAD response is always empty, so the length has to come from the header rather
+// than from the body
+fn content_length(headers: &http::HeaderMap) -> Option<u64> {
+    headers
+        .get(http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+// The length that a body should have once it has been read, which is only known if it isn't
+// encoded, since a backend may decompress it and Content-Length is the length before that
+fn decoded_length(headers: &http::HeaderMap) -> Option<u64> {
+    match headers.get(http::header::CONTENT_ENCODING) {
+        Some(encoding) if encoding != "identity" => None,
+        _ => content_length(headers),
+    }
 }
 
 /// A struct that serves as a Maptiler "session", which stores the API key and is used to create
 /// requests
+#[derive(Clone)]
 pub struct Maptiler {
     api_key: Arc<String>,
-    client: Arc<reqwest::Client>,
+    backend: Arc<dyn HttpBackend>,
+    quota: Option<Arc<QuotaTracker>>,
+    throttle: Option<Arc<Throttle>>,
+    circuit: Option<Arc<CircuitTracker>>,
+    scheduler: Option<Arc<Scheduler>>,
+    buffer_pool: Option<Arc<BufferPool>>,
+    corrupt_tile_retries: u32,
+    max_response_size: Option<u64>,
+    read_timeout: Option<Duration>,
+    base_urls: Arc<Vec<String>>,
+    dry_run: bool,
 }
 
 impl Maptiler {
@@ -401,13 +1239,11 @@ impl Maptiler {
     where
         S: Into<String>,
     {
-        Ok(Self {
-            api_key: Arc::new(api_key.into()),
-            client: Arc::new(reqwest::Client::builder().build()?),
-        })
+        Self::builder(api_key).build()
     }
 
     /// Initializes this Maptiler Cloud API session, with a user provided [`reqwest::Client`]
+    #[cfg(feature = "reqwest")]
     pub fn new_with_client<S>(
         api_key: S,
         client: Arc<reqwest::Client>,
@@ -415,10 +1251,20 @@ impl Maptiler {
     where
         S: Into<String>,
     {
-        Ok(Self {
-            api_key: Arc::new(api_key.into()),
-            client,
-        })
+        Self::builder(api_key).client(client).build()
+    }
+
+    /// Initializes this Maptiler Cloud API session from a loaded [`Config`]
+    pub fn from_config(config: &Config) -> Result<Self, errors::Error> {
+        config.builder()?.build()
+    }
+
+    /// Creates a builder to configure a Maptiler Cloud API session
+    pub fn builder<S>(api_key: S) -> MaptilerBuilder
+    where
+        S: Into<String>,
+    {
+        MaptilerBuilder::new(api_key.into())
     }
 
     /// Performs a generic request to the Maptiler Cloud API
@@ -430,16 +1276,216 @@ impl Maptiler {
         ConstructedRequest {
             api_key: Arc::clone(&self.api_key),
             inner: request.into(),
-            client: self.client.clone(),
+            backend: Arc::clone(&self.backend),
+            quota: self.quota.clone(),
+            throttle: self.throttle.clone(),
+            circuit: self.circuit.clone(),
+            scheduler: self.scheduler.clone(),
+            buffer_pool: self.buffer_pool.clone(),
+            priority: Priority::default(),
+            fallbacks: Vec::new(),
+            corrupt_tile_retries: self.corrupt_tile_retries,
+            max_response_size: self.max_response_size,
+            read_timeout: self.read_timeout,
+            base_urls: Arc::clone(&self.base_urls),
+            dry_run: self.dry_run,
         }
     }
 
     /// Performs a tile request to the Maptiler Cloud API
     pub fn create_tile_request(&self, tile_request: TileRequest) -> ConstructedRequest {
-        ConstructedRequest {
-            api_key: Arc::clone(&self.api_key),
-            inner: RequestType::TileRequest(tile_request),
-            client: self.client.clone(),
+        self.create_request(tile_request)
+    }
+
+    /// Hands a tile that is no longer needed back to the session, so that its memory can be reused
+    /// for the body of a later request
+    ///
+    /// This only does something if the session has a buffer pool, see
+    /// [`MaptilerBuilder::buffer_pool`]
+    ///
+    pub fn recycle(&self, buffer: Vec<u8>) {
+        if let Some(pool) = &self.buffer_pool {
+            pool.put(buffer);
         }
     }
+
+    /// Returns how many requests of the session may currently be in flight at once, if it limits
+    /// them
+    ///
+    /// With [`MaptilerBuilder::adaptive_concurrency`] this changes as requests finish
+    ///
+    pub fn concurrency_limit(&self) -> Option<usize> {
+        self.scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.max_concurrent())
+    }
+
+    /// Returns how much of the request budget has been used, if the session has a [`Quota`]
+    pub fn quota_usage(&self) -> Option<QuotaUsage> {
+        self.quota.as_ref().map(|quota| quota.usage())
+    }
+
+    /// Returns the URL template of a tileset, for use with web map libraries like Leaflet or
+    /// MapLibre
+    ///
+    /// The template uses the first base URL of this session, includes its API key, and leaves the {z}, {x}, and {y}
+    /// placeholders for the map library to fill in, ex:
+    ///
+    /// "https://api.maptiler.com/tiles/satellite/{z}/{x}/{y}.jpg?key=AAAAAAAAAAAAAAAAAA"
+    ///
+    pub fn url_template(&self, set: TileSet) -> String {
+        format!(
+            "{}/tiles/{}/{{z}}/{{x}}/{{y}}.{}?key={}",
+            self.base_urls[0],
+            encode_path_segment(set.endpoint()),
+            encode_path_segment(set.file_extension()),
+            &self.api_key
+        )
+    }
+
+    /// Returns the URL of the style JSON of a map style, for use with MapLibre
+    ///
+    /// The style is either a built-in map, such as "streets-v2", or a custom map from the
+    /// Maptiler Cloud account, ex:
+    ///
+    /// "https://api.maptiler.com/maps/streets-v2/style.json?key=AAAAAAAAAAAAAAAAAA"
+    ///
+    pub fn style_url(&self, style_id: &StyleId) -> String {
+        format!(
+            "{}/maps/{}/style.json?key={}",
+            self.base_urls[0], style_id, &self.api_key
+        )
+    }
+
+    /// Creates a [`TileSource`] that requests tiles of the given tileset using this session
+    pub fn tile_source(&self, set: TileSet) -> MaptilerTileSource {
+        MaptilerTileSource::new(self.clone(), set)
+    }
+
+    /// Requests and parses the TileJSON metadata of a tileset
+    ///
+    /// The result can be used with [`TileRequest::new_with_tilejson`] to validate tile requests
+    /// against the zoom levels and bounds that Maptiler Cloud currently reports, instead of the
+    /// hardcoded ones
+    ///
+    pub async fn fetch_tilejson(&self, set: TileSet) -> Result<TileJson, errors::Error> {
+        let bytes = self
+            .create_request(TileJsonRequest::new(set))
+            .execute()
+            .await?;
+
+        Ok(TileJson::from_slice(&bytes)?)
+    }
+
+    /// Returns the built-in tilesets that the API key of this session can access, in the order of
+    /// [`TileSet::BUILT_IN`]
+    ///
+    /// The TileJSON of every built-in tileset is requested at once. Tilesets whose TileJSON is
+    /// forbidden or not found are left out, such as those not included in the plan of the
+    /// account. Any other error, such as an invalid key or an unreachable server, is returned,
+    /// since then nothing can be said about the plan.
+    ///
+    /// Maptiler Cloud also answers 403 Forbidden for a key that is invalid or restricted, ex: to
+    /// other domains, so if every tileset is forbidden that error is returned instead of an empty
+    /// list.
+    ///
+    pub async fn available_tilesets(&self) -> Result<Vec<TileSet>, errors::Error> {
+        let mut probes: Vec<_> = TileSet::BUILT_IN
+            .iter()
+            .map(|&set| {
+                let constructed = self.create_request(TileJsonRequest::new(set));
+
+                (
+                    set,
+                    Box::pin(async move { constructed.execute().await }),
+                    None,
+                )
+            })
+            .collect();
+
+        // Polls every probe until all of them are done, without needing a particular async runtime
+        std::future::poll_fn(|cx| {
+            let mut done = true;
+
+            for (_, probe, result) in probes.iter_mut() {
+                if result.is_none() {
+                    match std::future::Future::poll(probe.as_mut(), cx) {
+                        Poll::Ready(probed) => *result = Some(probed),
+                        Poll::Pending => done = false,
+                    }
+                }
+            }
+
+            if done {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+
+        let mut available = Vec::new();
+        let mut forbidden = 0;
+
+        for (set, _, result) in probes {
+            match result {
+                Some(Ok(_)) => available.push(set),
+                Some(Err(errors::Error::Http(http::StatusCode::FORBIDDEN))) => forbidden += 1,
+                Some(Err(errors::Error::Http(http::StatusCode::NOT_FOUND))) => {}
+                Some(Err(e)) => return Err(e),
+                None => unreachable!("every probe has finished"),
+            }
+        }
+
+        // No plan forbids everything, so it was the key itself that was refused
+        if forbidden == TileSet::BUILT_IN.len() {
+            return Err(errors::Error::Http(http::StatusCode::FORBIDDEN));
+        }
+
+        Ok(available)
+    }
+
+    /// Requests the style JSON of a map style
+    pub async fn fetch_style(
+        &self,
+        style_id: &StyleId,
+    ) -> Result<serde_json::Value, errors::Error> {
+        let bytes = self
+            .create_request(StyleRequest::new(style_id.clone()))
+            .execute()
+            .await?;
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Requests the style JSON of a map style, and rewrites it to load its tiles, fonts, and
+    /// sprites from `local_base_url` instead of Maptiler Cloud, see [`style::localize`]
+    ///
+    /// URLs of every base URL of the session are rewritten, and none of them keep the API key, so
+    /// the style can be handed to browsers or used offline
+    ///
+    pub async fn fetch_local_style(
+        &self,
+        style_id: &StyleId,
+        local_base_url: &str,
+    ) -> Result<serde_json::Value, errors::Error> {
+        let mut style = self.fetch_style(style_id).await?;
+        let local_base_url = local_base_url.trim_end_matches('/');
+
+        for base_url in self.base_urls.iter() {
+            style::localize(&mut style, base_url, local_base_url);
+        }
+
+        Ok(style)
+    }
+
+    /// Requests a tile, typed by the file format of its tileset
+    ///
+    /// This works the same as executing a request for the tile, see [`Tile`]
+    ///
+    pub async fn fetch_tile(&self, tile_request: TileRequest) -> Result<Tile, errors::Error> {
+        let bytes = self.create_request(tile_request).execute().await?;
+
+        Ok(Tile::new(tile_request.set, bytes))
+    }
 }
@@ -0,0 +1,240 @@
+//! An embedded HTTP server for testing against maptiler-cloud without an API key or network
+//!
+//! The server answers mocked paths with canned responses, answers everything else with 404 Not
+//! Found, and records every request that it receives. Point a session at it using
+//! [`MaptilerBuilder::base_urls`](crate::MaptilerBuilder::base_urls).
+//!
+//! # Example
+//!
+//! ```
+//! use maptiler_cloud::{mock::MockServer, Maptiler, TileRequest, TileSet};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockServer::start().await.unwrap();
+//!
+//!     let tile_request = TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap();
+//!     server.mock_request(tile_request, vec![0xFF, 0xD8, 0xFF, 0xD9]);
+//!
+//!     let maptiler = Maptiler::builder("placeholder api key")
+//!         .base_urls(vec![server.base_url()])
+//!         .build()
+//!         .unwrap();
+//!
+//!     let tile = maptiler.create_request(tile_request).execute().await.unwrap();
+//!
+//!     assert_eq!(tile, vec![0xFF, 0xD8, 0xFF, 0xD9]);
+//!     assert_eq!(server.requests()[0].path, "/tiles/satellite/0/0/0.jpg");
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use tokio::sync::oneshot;
+
+use crate::{fixtures, RequestType};
+
+/// A canned response for [`MockServer`] to answer with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    /// The HTTP status code to answer with
+    pub status: http::StatusCode,
+    /// The headers to answer with. Content-Length is always added by the server.
+    pub headers: http::HeaderMap,
+    /// The body to answer with
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// Creates a 200 OK response with the given body
+    pub fn ok<B: Into<Vec<u8>>>(body: B) -> Self {
+        Self {
+            status: http::StatusCode::OK,
+            headers: http::HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Creates an empty response with the given status code
+    pub fn status(status: http::StatusCode) -> Self {
+        Self {
+            status,
+            headers: http::HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a header to the response
+    pub fn header(mut self, name: http::header::HeaderName, value: &str) -> Self {
+        if let Ok(value) = http::HeaderValue::from_str(value) {
+            self.headers.insert(name, value);
+        }
+
+        self
+    }
+}
+
+/// A request that [`MockServer`] received
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// The method of the request
+    pub method: http::Method,
+    /// The path of the request, ex: "/tiles/satellite/0/0/0.jpg"
+    pub path: String,
+    /// The query string of the request, which holds the API key, ex: "key=AAAAAAAAAAAAAAAAAA"
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    responses: HashMap<String, MockResponse>,
+    requests: Vec<RecordedRequest>,
+    serve_fixtures: bool,
+}
+
+/// An embedded HTTP server that serves canned responses and records requests
+///
+/// The server runs on the tokio runtime that started it, and shuts down when it is dropped
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Starts a new server on a free port of 127.0.0.1
+    pub async fn start() -> Result<Self, hyper::Error> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let service_state = Arc::clone(&state);
+
+        let make_service = make_service_fn(move |_connection| {
+            let state = Arc::clone(&service_state);
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let response = respond(&state, request);
+
+                    async move { Ok::<_, Infallible>(response) }
+                }))
+            }
+        });
+
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))?.serve(make_service);
+        let addr = server.local_addr();
+
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+        let server = server.with_graceful_shutdown(async {
+            shutdown_signal.await.ok();
+        });
+
+        tokio::spawn(server);
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Returns the base URL of the server, ex: "http://127.0.0.1:41234"
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Returns the address that the server is listening on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Answers requests for the given path with the given response
+    ///
+    /// The path doesn't include the query string, ex: "/tiles/satellite/0/0/0.jpg". Mocking the
+    /// same path again replaces its response.
+    ///
+    pub fn mock<P: Into<String>>(&self, path: P, response: MockResponse) {
+        self.lock().responses.insert(path.into(), response);
+    }
+
+    /// Answers the given request, such as a [`TileRequest`](crate::TileRequest), with a 200 OK
+    /// response holding the given body
+    pub fn mock_request<R, B>(&self, request: R, body: B)
+    where
+        R: Into<RequestType>,
+        B: Into<Vec<u8>>,
+    {
+        self.mock(request.into().path(), MockResponse::ok(body));
+    }
+
+    /// Answers every tile request that isn't mocked with the [`fixtures`] tile of its format
+    ///
+    /// This lets examples and tests request any tile of any built-in tileset fully offline
+    ///
+    pub fn serve_fixtures(&self) {
+        self.lock().serve_fixtures = true;
+    }
+
+    /// Returns every request that the server has received so far, oldest first
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+fn respond(state: &Mutex<State>, request: Request<Body>) -> Response<Body> {
+    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+
+    let path = request.uri().path().to_string();
+
+    state.requests.push(RecordedRequest {
+        method: request.method().clone(),
+        path: path.clone(),
+        query: request.uri().query().map(String::from),
+    });
+
+    let mock = match state.responses.get(&path) {
+        Some(mock) => mock.clone(),
+        None => state
+            .serve_fixtures
+            .then(|| fixture_for_path(&path))
+            .flatten()
+            .map(MockResponse::ok)
+            .unwrap_or_else(|| MockResponse::status(http::StatusCode::NOT_FOUND)),
+    };
+
+    let mut response = Response::new(Body::from(mock.body));
+    *response.status_mut() = mock.status;
+    *response.headers_mut() = mock.headers;
+
+    response
+}
+
+// Finds the fixture for a tile path, ex: "/tiles/satellite/0/0/0.jpg"
+fn fixture_for_path(path: &str) -> Option<&'static [u8]> {
+    let file_name = path.strip_prefix("/tiles/")?.rsplit('/').next()?;
+
+    // Extensions may contain dots themselves, ex: "0.quantized-mesh-1.0"
+    let (_, extension) = file_name.split_once('.')?;
+
+    fixtures::for_extension(extension)
+}
@@ -0,0 +1,150 @@
+//! A non-async facade over a [`TileSource`], for immediate-mode GUIs
+//!
+//! Widgets in immediate-mode GUIs such as egui ask for the tiles they draw every frame, and can't
+//! wait for them. [`PollingTiles::get_tile`] answers right away with whatever is known about a
+//! tile, and starts downloading it on a tokio runtime the first time it is asked for.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use maptiler_cloud::{poll::{PollingTiles, TileState}, Maptiler, TileSet};
+//!
+//! let runtime = tokio::runtime::Runtime::new().unwrap();
+//!
+//! let maptiler = Maptiler::new("placeholder api key").unwrap();
+//! let source = maptiler.tile_source(TileSet::Satellite);
+//! let tiles = PollingTiles::new(source, runtime.handle().clone());
+//!
+//! // Called every frame
+//! match tiles.get_tile(2, 1, 1) {
+//!     TileState::Loaded(jpg) => { /* draw the tile */ }
+//!     TileState::Loading => { /* draw a placeholder */ }
+//!     TileState::Failed(e) => eprintln!("{}", e),
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use tokio::runtime::Handle;
+
+use crate::TileSource;
+
+/// What is known about a tile of [`PollingTiles`]
+#[derive(Debug)]
+pub enum TileState<E> {
+    /// The tile is being downloaded
+    Loading,
+    /// The tile has been downloaded
+    Loaded(Bytes),
+    /// The tile could not be downloaded, see [`PollingTiles::forget`] for trying again
+    Failed(Arc<E>),
+}
+
+impl<E> Clone for TileState<E> {
+    fn clone(&self) -> Self {
+        match self {
+            TileState::Loading => TileState::Loading,
+            TileState::Loaded(bytes) => TileState::Loaded(bytes.clone()),
+            TileState::Failed(e) => TileState::Failed(Arc::clone(e)),
+        }
+    }
+}
+
+// Every tile that has been asked for, by zoom level and coordinates
+type TileMap<E> = HashMap<(u32, u32, u32), TileState<E>>;
+
+/// Tiles of a [`TileSource`] that can be asked for without waiting
+///
+/// Downloaded tiles are kept until they are forgotten, see [`PollingTiles::forget`] and
+/// [`PollingTiles::retain`]. Clones share the same tiles.
+///
+pub struct PollingTiles<S: TileSource> {
+    source: Arc<S>,
+    runtime: Handle,
+    tiles: Arc<Mutex<TileMap<S::Error>>>,
+}
+
+impl<S: TileSource> Clone for PollingTiles<S> {
+    fn clone(&self) -> Self {
+        Self {
+            source: Arc::clone(&self.source),
+            runtime: self.runtime.clone(),
+            tiles: Arc::clone(&self.tiles),
+        }
+    }
+}
+
+impl<S> PollingTiles<S>
+where
+    S: TileSource + Send + Sync + 'static,
+    S::Error: Send + Sync + 'static,
+{
+    /// Creates new PollingTiles that download from the given source on the given tokio runtime
+    pub fn new(source: S, runtime: Handle) -> Self {
+        Self {
+            source: Arc::new(source),
+            runtime,
+            tiles: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns what is known about the tile at the given zoom level and coordinates, starting to
+    /// download it if it hasn't been asked for yet
+    ///
+    /// This never blocks on the network
+    ///
+    pub fn get_tile(&self, zoom: u32, x: u32, y: u32) -> TileState<S::Error> {
+        let mut tiles = self.lock();
+
+        if let Some(state) = tiles.get(&(zoom, x, y)) {
+            return state.clone();
+        }
+
+        tiles.insert((zoom, x, y), TileState::Loading);
+        drop(tiles);
+
+        let source = Arc::clone(&self.source);
+        let tiles = Arc::clone(&self.tiles);
+
+        self.runtime.spawn(async move {
+            let state = match source.get_tile(zoom, x, y).await {
+                Ok(bytes) => TileState::Loaded(Bytes::from(bytes)),
+                Err(e) => TileState::Failed(Arc::new(e)),
+            };
+
+            let mut tiles = tiles.lock().unwrap_or_else(|e| e.into_inner());
+
+            // The tile may have been forgotten while it was downloading
+            if let Some(entry) = tiles.get_mut(&(zoom, x, y)) {
+                *entry = state;
+            }
+        });
+
+        TileState::Loading
+    }
+
+    /// Forgets a tile, so that the next [`PollingTiles::get_tile`] downloads it again
+    ///
+    /// This is how a failed tile is retried
+    ///
+    pub fn forget(&self, zoom: u32, x: u32, y: u32) {
+        self.lock().remove(&(zoom, x, y));
+    }
+
+    /// Keeps only the tiles for which the given function returns true, such as those near the
+    /// view, and forgets the rest
+    pub fn retain<F>(&self, mut keep: F)
+    where
+        F: FnMut(u32, u32, u32) -> bool,
+    {
+        self.lock().retain(|&(zoom, x, y), _| keep(zoom, x, y));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TileMap<S::Error>> {
+        self.tiles.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Mutex;
+
+// A pool of emptied response body buffers, shared by every request of a session
+//
+// Downloading tens of thousands of tiles otherwise allocates and frees a buffer for each one
+#[derive(Debug)]
+pub(crate) struct BufferPool {
+    max_buffers: usize,
+    buffers: Mutex<Vec<Vec<u8>>>,
+}
+
+impl BufferPool {
+    pub(crate) fn new(max_buffers: usize) -> Self {
+        Self {
+            max_buffers,
+            buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+        }
+    }
+
+    // Takes an empty buffer out of the pool that can hold at least `capacity` bytes, or allocates
+    // a new one if the pool is empty
+    //
+    // The capacity is reserved right away, so it must not come straight from a response header
+    pub(crate) fn take(&self, capacity: usize) -> Vec<u8> {
+        let mut buffer = self.lock().pop().unwrap_or_default();
+        buffer.reserve(capacity);
+        buffer
+    }
+
+    // Returns a buffer to the pool, unless the pool is already full
+    pub(crate) fn put(&self, mut buffer: Vec<u8>) {
+        let mut buffers = self.lock();
+
+        if buffers.len() < self.max_buffers {
+            buffer.clear();
+            buffers.push(buffer);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+        self.buffers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
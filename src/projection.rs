@@ -0,0 +1,131 @@
+//! Conversions between longitude and latitude, and the Web Mercator projection that tiles use
+//!
+//! Longitudes and latitudes are in degrees on WGS 84 ([EPSG:4326](https://epsg.io/4326)), and
+//! are always given longitude first, like in GeoJSON and TileJSON bounds. Web Mercator
+//! ([EPSG:3857](https://epsg.io/3857)) coordinates are either in meters from where the equator
+//! crosses the prime meridian, or in pixels from the top left corner of the map at a zoom level.
+//!
+//! Web Mercator can't show the poles, so latitudes are clamped to [`MAX_LATITUDE`].
+//!
+//! # Example
+//!
+//! ```
+//! use maptiler_cloud::projection;
+//!
+//! // Bern, Switzerland
+//! let (x, y) = projection::to_meters(7.4474, 46.9481);
+//!
+//! let (longitude, latitude) = projection::from_meters(x, y);
+//! assert!((longitude - 7.4474).abs() < 1e-9);
+//! assert!((latitude - 46.9481).abs() < 1e-9);
+//!
+//! let (x, y) = projection::tile_at(7.4474, 46.9481, 10);
+//! assert_eq!((x.get(), y.get()), (533, 360));
+//! ```
+
+use std::f64::consts::PI;
+
+use crate::{TileX, TileY, Zoom};
+
+/// The radius of the sphere that Web Mercator projects onto, which is the equatorial radius of
+/// WGS 84, in meters
+pub const EARTH_RADIUS: f64 = 6_378_137.0;
+
+/// The latitude in degrees at which Web Mercator maps are cut off, so that the world is square
+pub const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// How far the edges of the map are from its center in meters, in every direction
+pub const MAX_EXTENT: f64 = PI * EARTH_RADIUS;
+
+/// Projects a point to Web Mercator meters, where x grows to the east and y to the north
+pub fn to_meters(longitude: f64, latitude: f64) -> (f64, f64) {
+    let latitude = latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+
+    let x = EARTH_RADIUS * longitude.to_radians();
+    let y = EARTH_RADIUS * latitude.tan().asinh();
+
+    (x, y)
+}
+
+/// Converts Web Mercator meters back to a point, returning its (longitude, latitude)
+pub fn from_meters(x: f64, y: f64) -> (f64, f64) {
+    let longitude = (x / EARTH_RADIUS).to_degrees();
+    let latitude = (y / EARTH_RADIUS).sinh().atan().to_degrees();
+
+    (longitude, latitude)
+}
+
+/// Projects a point onto the square of the whole map, where (0, 0) is the top left corner and
+/// (1, 1) is the bottom right
+///
+/// Multiplying by the size of the map in pixels or tiles at a zoom level gives the pixel or tile
+/// position of the point
+///
+pub fn to_unit(longitude: f64, latitude: f64) -> (f64, f64) {
+    let (x, y) = to_meters(longitude, latitude);
+
+    (
+        (x + MAX_EXTENT) / (2.0 * MAX_EXTENT),
+        (MAX_EXTENT - y) / (2.0 * MAX_EXTENT),
+    )
+}
+
+/// Converts a position on the square of the whole map back to a point, see [`to_unit`]
+pub fn from_unit(x: f64, y: f64) -> (f64, f64) {
+    from_meters(
+        x * 2.0 * MAX_EXTENT - MAX_EXTENT,
+        MAX_EXTENT - y * 2.0 * MAX_EXTENT,
+    )
+}
+
+/// Projects a point to pixels from the top left corner of the map, at a zoom level where tiles
+/// are `tile_size` pixels wide, usually 256 or 512
+pub fn to_pixels(
+    longitude: f64,
+    latitude: f64,
+    zoom: impl Into<Zoom>,
+    tile_size: u32,
+) -> (f64, f64) {
+    let map_size = map_size(zoom.into(), tile_size);
+    let (x, y) = to_unit(longitude, latitude);
+
+    (x * map_size, y * map_size)
+}
+
+/// Converts pixels from the top left corner of the map back to a point, see [`to_pixels`]
+pub fn from_pixels(x: f64, y: f64, zoom: impl Into<Zoom>, tile_size: u32) -> (f64, f64) {
+    let map_size = map_size(zoom.into(), tile_size);
+
+    from_unit(x / map_size, y / map_size)
+}
+
+/// Returns the x and y coordinates of the tile that a point is on at a zoom level
+///
+/// Points on the antimeridian or past the edge of the map are on the last tile of their row or
+/// column
+///
+pub fn tile_at(longitude: f64, latitude: f64, zoom: impl Into<Zoom>) -> (TileX, TileY) {
+    let tiles_per_side = map_size(zoom.into(), 1);
+    let (x, y) = to_unit(longitude, latitude);
+
+    let tile = |position: f64| (position * tiles_per_side).clamp(0.0, tiles_per_side - 1.0) as u32;
+
+    (TileX::new(tile(x)), TileY::new(tile(y)))
+}
+
+/// Returns the area that a tile covers, as `[west, south, east, north]` in degrees like in
+/// TileJSON
+pub fn tile_bounds(zoom: impl Into<Zoom>, x: impl Into<TileX>, y: impl Into<TileY>) -> [f64; 4] {
+    let tiles_per_side = map_size(zoom.into(), 1);
+    let (x, y) = (f64::from(x.into().get()), f64::from(y.into().get()));
+
+    let (west, north) = from_unit(x / tiles_per_side, y / tiles_per_side);
+    let (east, south) = from_unit((x + 1.0) / tiles_per_side, (y + 1.0) / tiles_per_side);
+
+    [west, south, east, north]
+}
+
+// The width and height of the whole map in pixels at a zoom level
+fn map_size(zoom: Zoom, tile_size: u32) -> f64 {
+    f64::from(tile_size) * 2f64.powi(zoom.get() as i32)
+}
@@ -0,0 +1,167 @@
+//! Python bindings, built with the `python` feature
+//!
+//! The crate is built as a Python extension module named `maptiler_cloud`, ex: with
+//! [maturin](https://www.maturin.rs/), which builds it as a shared library even though the crate
+//! is only a Rust library by default:
+//!
+//! ```text
+//! maturin develop --features python
+//! ```
+//!
+//! Requests block the calling Python thread, but release the GIL while they are waiting on the
+//! network, so other Python threads keep running.
+//!
+//! ```python
+//! import maptiler_cloud
+//!
+//! maptiler = maptiler_cloud.Maptiler("placeholder api key")
+//!
+//! request = maptiler_cloud.TileRequest("satellite", 1, 1, 2)
+//! jpg = maptiler.get_tile(request)
+//! ```
+
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+
+use crate::{errors, Config, Maptiler, TileJsonRequest, TileRequest, TileSet};
+
+/// A request for a tile of a built-in tileset, which is checked when it is created
+#[pyclass(name = "TileRequest", module = "maptiler_cloud", frozen)]
+#[derive(Clone)]
+struct PyTileRequest(TileRequest);
+
+#[pymethods]
+impl PyTileRequest {
+    #[new]
+    fn new(tileset: &str, x: u32, y: u32, zoom: u32) -> PyResult<Self> {
+        let tile_request = TileRequest::new(parse_tileset(tileset)?, x, y, zoom).map_err(to_py)?;
+
+        Ok(Self(tile_request))
+    }
+
+    #[getter]
+    fn tileset(&self) -> &'static str {
+        self.0.set.endpoint()
+    }
+
+    #[getter]
+    fn x(&self) -> u32 {
+        self.0.x()
+    }
+
+    #[getter]
+    fn y(&self) -> u32 {
+        self.0.y()
+    }
+
+    #[getter]
+    fn zoom(&self) -> u32 {
+        self.0.zoom()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TileRequest({:?}, {}, {}, {})",
+            self.tileset(),
+            self.x(),
+            self.y(),
+            self.zoom()
+        )
+    }
+}
+
+/// A Maptiler Cloud session, which stores the API key and sends requests
+#[pyclass(name = "Maptiler", module = "maptiler_cloud", frozen)]
+struct PyMaptiler {
+    maptiler: Maptiler,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[pymethods]
+impl PyMaptiler {
+    #[new]
+    fn new(api_key: String) -> PyResult<Self> {
+        Self::with_session(Maptiler::new(api_key))
+    }
+
+    /// Creates a session from a JSON or TOML configuration file
+    #[staticmethod]
+    fn from_config(path: &str) -> PyResult<Self> {
+        let config = Config::from_file(path).map_err(to_py)?;
+
+        Self::with_session(Maptiler::from_config(&config))
+    }
+
+    /// Downloads a tile, returning its raw bytes
+    fn get_tile<'py>(
+        &self,
+        py: Python<'py>,
+        request: &PyTileRequest,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let constructed = self.maptiler.create_request(request.0);
+
+        let bytes = py
+            .allow_threads(|| self.runtime.block_on(constructed.execute()))
+            .map_err(to_py)?;
+
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Downloads the TileJSON metadata of a tileset, returning it as a JSON string
+    fn tilejson(&self, py: Python<'_>, tileset: &str) -> PyResult<String> {
+        let constructed = self
+            .maptiler
+            .create_request(TileJsonRequest::new(parse_tileset(tileset)?));
+
+        let bytes = py
+            .allow_threads(|| self.runtime.block_on(constructed.execute()))
+            .map_err(to_py)?;
+
+        String::from_utf8(bytes).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    /// Returns the URL template of a tileset, for use with web map libraries
+    fn url_template(&self, tileset: &str) -> PyResult<String> {
+        Ok(self.maptiler.url_template(parse_tileset(tileset)?))
+    }
+}
+
+impl PyMaptiler {
+    fn with_session(maptiler: Result<Maptiler, errors::Error>) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+        Ok(Self {
+            maptiler: maptiler.map_err(to_py)?,
+            runtime,
+        })
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "maptiler_cloud")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMaptiler>()?;
+    module.add_class::<PyTileRequest>()?;
+
+    Ok(())
+}
+
+fn parse_tileset(endpoint: &str) -> PyResult<TileSet> {
+    TileSet::from_endpoint(endpoint)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown tileset {}", endpoint)))
+}
+
+// Invalid arguments are a ValueError, like in the rest of Python, and everything else is a
+// RuntimeError
+fn to_py<E: Into<errors::Error>>(e: E) -> PyErr {
+    match e.into() {
+        e @ errors::Error::Argument(_) => PyValueError::new_err(e.to_string()),
+        e => PyRuntimeError::new_err(e.to_string()),
+    }
+}
@@ -0,0 +1,105 @@
+//! A bounded queue for downloading many tiles without buffering all of them in memory
+//!
+//! Producers enumerating a large area submit requests one at a time, and each submission waits
+//! while the queue is full. This slows the producer down to the speed of the downloads, instead of
+//! letting it build up millions of pending futures.
+
+use std::sync::Arc;
+
+use crate::{
+    errors,
+    scheduler::{Permit, Scheduler},
+    ConstructedRequest,
+};
+
+/// A queue that holds at most a fixed number of downloads at once
+///
+/// Clones of the queue share the same capacity. Waiting submissions get room in order of the
+/// [`Priority`](crate::Priority) of their requests.
+///
+/// # Example
+///
+/// ```no_run
+/// use maptiler_cloud::{DownloadQueue, Maptiler, TileRequest, TileSet};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let maptiler = Maptiler::new("placeholder api key").unwrap();
+///     let queue = DownloadQueue::new(16);
+///
+///     for x in 0..1024 {
+///         let tile_request = TileRequest::new(TileSet::Satellite, x, 0, 10).unwrap();
+///
+///         // This waits while 16 downloads are already queued
+///         let download = queue.submit(maptiler.create_request(tile_request)).await;
+///
+///         tokio::spawn(async move {
+///             let tile = download.execute().await.unwrap();
+///         });
+///     }
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct DownloadQueue {
+    slots: Arc<Scheduler>,
+}
+
+impl DownloadQueue {
+    /// Creates a new queue that holds at most `depth` downloads at once
+    ///
+    /// A depth of 0 is treated as 1
+    ///
+    pub fn new(depth: usize) -> Self {
+        Self {
+            slots: Arc::new(Scheduler::new(depth)),
+        }
+    }
+
+    /// Returns how many downloads the queue holds at most
+    pub fn depth(&self) -> usize {
+        self.slots.max_concurrent()
+    }
+
+    /// Waits until the queue has room for the request, then adds it to the queue
+    ///
+    /// The request keeps its place in the queue until the returned [`QueuedDownload`] has been
+    /// executed or dropped
+    ///
+    pub async fn submit(&self, request: ConstructedRequest) -> QueuedDownload {
+        let permit = self.slots.acquire(request.priority()).await;
+
+        QueuedDownload {
+            request,
+            _permit: permit,
+        }
+    }
+}
+
+/// A request that has been given a place in a [`DownloadQueue`]
+///
+/// This is created using [`DownloadQueue::submit`]
+pub struct QueuedDownload {
+    request: ConstructedRequest,
+    _permit: Permit,
+}
+
+impl QueuedDownload {
+    /// Returns the request that is queued
+    pub fn request(&self) -> &ConstructedRequest {
+        &self.request
+    }
+
+    /// Performs the request, see [`ConstructedRequest::execute`], and then makes room in the queue
+    pub async fn execute(self) -> Result<Vec<u8>, errors::Error> {
+        self.request.execute().await
+    }
+}
+
+impl std::fmt::Debug for QueuedDownload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueuedDownload")
+            .field("request", &self.request)
+            .finish_non_exhaustive()
+    }
+}
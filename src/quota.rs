@@ -0,0 +1,138 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{backend::HttpBackend, errors};
+
+/// A budget of requests that a Maptiler session may make per time window
+///
+/// For example, a budget that matches a plan with 100,000 requests per month would be
+/// `Quota::new(100_000, Duration::from_secs(30 * 24 * 60 * 60))`. Every request counts against
+/// the budget, including HEAD requests, just like Maptiler Cloud counts them.
+///
+/// By default, requests that would exceed the budget fail with [`errors::Error::QuotaExceeded`].
+/// Use [`Quota::wait_for_window`] to pause them until the next window starts instead, which is
+/// useful for short windows such as a per-minute limit.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    limit: u64,
+    window: Duration,
+    wait: bool,
+}
+
+impl Quota {
+    /// Creates a new Quota that allows `limit` requests every `window`
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            wait: false,
+        }
+    }
+
+    /// Pauses requests that would exceed the budget until the next window starts, instead of
+    /// failing them
+    pub fn wait_for_window(mut self) -> Self {
+        self.wait = true;
+        self
+    }
+
+    /// Returns the number of requests allowed per window
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the length of a window
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+/// A snapshot of how much of a [`Quota`] has been used in the current window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaUsage {
+    /// The number of requests made in the current window
+    pub used: u64,
+    /// The number of requests allowed per window
+    pub limit: u64,
+    /// The time left until the current window ends and the usage is reset
+    pub resets_in: Duration,
+}
+
+impl QuotaUsage {
+    /// Returns the number of requests that can still be made in the current window
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.used)
+    }
+}
+
+// Tracks the requests made against a Quota, shared by every request of a session
+#[derive(Debug)]
+pub(crate) struct QuotaTracker {
+    quota: Quota,
+    state: Mutex<WindowState>,
+}
+
+#[derive(Debug)]
+struct WindowState {
+    start: Instant,
+    used: u64,
+}
+
+impl QuotaTracker {
+    pub(crate) fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            state: Mutex::new(WindowState {
+                start: Instant::now(),
+                used: 0,
+            }),
+        }
+    }
+
+    // Takes one request out of the budget, waiting for the next window if the quota allows it
+    pub(crate) async fn acquire(&self, backend: &dyn HttpBackend) -> Result<(), errors::Error> {
+        loop {
+            let resets_in = {
+                let mut state = self.lock();
+
+                if state.used < self.quota.limit {
+                    state.used += 1;
+                    return Ok(());
+                }
+
+                if !self.quota.wait {
+                    return Err(errors::Error::QuotaExceeded(self.quota.limit));
+                }
+
+                self.quota.window.saturating_sub(state.start.elapsed())
+            };
+
+            backend.sleep(resets_in).await;
+        }
+    }
+
+    pub(crate) fn usage(&self) -> QuotaUsage {
+        let state = self.lock();
+
+        QuotaUsage {
+            used: state.used,
+            limit: self.quota.limit,
+            resets_in: self.quota.window.saturating_sub(state.start.elapsed()),
+        }
+    }
+
+    // Locks the state, starting a new window first if the current one has ended
+    fn lock(&self) -> std::sync::MutexGuard<'_, WindowState> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if state.start.elapsed() >= self.quota.window {
+            state.start = Instant::now();
+            state.used = 0;
+        }
+
+        state
+    }
+}
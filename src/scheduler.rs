@@ -0,0 +1,331 @@
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// How urgently a request should be sent when it has to wait for others
+///
+/// When a session limits how many requests may run at once, see
+/// [`MaptilerBuilder::max_concurrent_requests`](crate::MaptilerBuilder::max_concurrent_requests),
+/// waiting requests are started highest priority first, and in the order that they started
+/// waiting within a priority
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    /// Background work, such as prefetching tiles that may be needed later
+    Low,
+    /// The default priority
+    #[default]
+    Normal,
+    /// Work that the user is waiting for, such as tiles in the visible viewport
+    High,
+}
+
+/// The settings of an adaptive concurrency limit, which finds out how many requests a session can
+/// run at once without overloading the API or the connection
+///
+/// The limit starts at `min` and grows by one each time a full limit's worth of requests in a row
+/// succeed within `target_latency`, up to `max`. A request that is slower than that, times out,
+/// can't connect, or is answered with 429 Too Many Requests or a 5xx status code halves the limit,
+/// down to `min`. This is the additive increase, multiplicative decrease (AIMD) rule that TCP uses
+/// to share a connection.
+///
+/// After halving, the requests that were already in flight at the old limit aren't counted as
+/// overload again, so a single burst only halves the limit once.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveConcurrency {
+    min: usize,
+    max: usize,
+    target_latency: Duration,
+}
+
+impl AdaptiveConcurrency {
+    /// Creates a new AdaptiveConcurrency that keeps the limit between `min` and `max`, and treats
+    /// responses slower than `target_latency` as a sign of overload
+    ///
+    /// A `min` of 0 is treated as 1, and a `max` below `min` as `min`
+    ///
+    pub fn new(min: usize, max: usize, target_latency: Duration) -> Self {
+        let min = min.max(1);
+
+        Self {
+            min,
+            max: max.max(min),
+            target_latency,
+        }
+    }
+
+    /// Returns the lowest that the limit goes
+    pub fn min(&self) -> usize {
+        self.min
+    }
+
+    /// Returns the highest that the limit goes
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Returns how long a response may take before it counts as overload
+    pub fn target_latency(&self) -> Duration {
+        self.target_latency
+    }
+}
+
+// Limits how many requests of a session run at once, handing out free slots by priority
+//
+// This doesn't depend on any async runtime, so it works with any HttpBackend
+#[derive(Debug)]
+pub(crate) struct Scheduler {
+    adaptive: Option<AdaptiveConcurrency>,
+    state: Mutex<SchedulerState>,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    limit: usize,
+    running: usize,
+    waiting: BinaryHeap<Waiter>,
+    next_sequence: u64,
+    // Requests in a row that finished in time since the limit last grew
+    successes: usize,
+    // Requests left to finish before overload can halve the limit again
+    recovering: usize,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    priority: Priority,
+    sequence: u64,
+    slot: Arc<Mutex<Slot>>,
+}
+
+// The highest priority waiter comes out of the heap first, and the oldest one within a priority
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+#[derive(Debug)]
+enum Slot {
+    Waiting(Option<Waker>),
+    Granted,
+    Cancelled,
+}
+
+impl Scheduler {
+    pub(crate) fn new(max_concurrent: usize) -> Self {
+        Self {
+            adaptive: None,
+            state: Mutex::new(SchedulerState {
+                // A limit of zero would never let anything run
+                limit: max_concurrent.max(1),
+                ..SchedulerState::default()
+            }),
+        }
+    }
+
+    pub(crate) fn adaptive(adaptive: AdaptiveConcurrency) -> Self {
+        Self {
+            adaptive: Some(adaptive),
+            state: Mutex::new(SchedulerState {
+                limit: adaptive.min,
+                ..SchedulerState::default()
+            }),
+        }
+    }
+
+    // Returns the current limit, which only changes if the scheduler is adaptive
+    pub(crate) fn max_concurrent(&self) -> usize {
+        self.lock().limit
+    }
+
+    // Adjusts an adaptive limit after a request finished, taking `latency` to answer
+    pub(crate) fn record(&self, latency: Duration, overloaded: bool) {
+        let adaptive = match &self.adaptive {
+            Some(adaptive) => adaptive,
+            None => return,
+        };
+
+        let mut state = self.lock();
+        state.recovering = state.recovering.saturating_sub(1);
+
+        if overloaded || latency > adaptive.target_latency {
+            state.successes = 0;
+
+            if state.recovering == 0 {
+                state.recovering = state.limit;
+                state.limit = (state.limit / 2).max(adaptive.min);
+            }
+        } else {
+            state.successes += 1;
+
+            if state.successes >= state.limit && state.limit < adaptive.max {
+                state.successes = 0;
+                state.limit += 1;
+
+                // The new slot goes to a waiter right away, if there is one
+                if Self::grant_next(&mut state) {
+                    state.running += 1;
+                }
+            }
+        }
+    }
+
+    // Waits for a free slot, which is held until the returned permit is dropped
+    pub(crate) fn acquire(self: &Arc<Self>, priority: Priority) -> Acquire {
+        Acquire {
+            scheduler: Arc::clone(self),
+            priority,
+            slot: None,
+        }
+    }
+
+    // Hands a finished request's slot to the next waiter, or frees it if nobody is waiting
+    fn release(&self) {
+        let mut state = self.lock();
+
+        // Slots over a limit that has shrunk are freed instead of handed over
+        if state.running <= state.limit && Self::grant_next(&mut state) {
+            return;
+        }
+
+        state.running -= 1;
+    }
+
+    // Wakes the next waiter that hasn't been cancelled with a slot, returning false if there is
+    // none
+    fn grant_next(state: &mut SchedulerState) -> bool {
+        while let Some(waiter) = state.waiting.pop() {
+            let mut slot = waiter.slot.lock().unwrap_or_else(|e| e.into_inner());
+
+            if let Slot::Waiting(waker) = &mut *slot {
+                let waker = waker.take();
+                *slot = Slot::Granted;
+
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SchedulerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub(crate) struct Acquire {
+    scheduler: Arc<Scheduler>,
+    priority: Priority,
+    slot: Option<Arc<Mutex<Slot>>>,
+}
+
+impl Future for Acquire {
+    type Output = Permit;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let scheduler = Arc::clone(&self.scheduler);
+
+        match &self.slot {
+            None => {
+                let mut state = scheduler.lock();
+
+                // Only skip the queue if nobody is already waiting in it
+                if state.running < state.limit && state.waiting.is_empty() {
+                    state.running += 1;
+                    drop(state);
+                    return Poll::Ready(Permit { scheduler });
+                }
+
+                let slot = Arc::new(Mutex::new(Slot::Waiting(Some(cx.waker().clone()))));
+                let sequence = state.next_sequence;
+                state.next_sequence += 1;
+
+                state.waiting.push(Waiter {
+                    priority: self.priority,
+                    sequence,
+                    slot: Arc::clone(&slot),
+                });
+
+                drop(state);
+                self.slot = Some(slot);
+
+                Poll::Pending
+            }
+            Some(slot) => {
+                let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+
+                match &mut *slot {
+                    Slot::Granted => {
+                        drop(slot);
+                        self.slot = None;
+                        Poll::Ready(Permit { scheduler })
+                    }
+                    Slot::Waiting(waker) => {
+                        *waker = Some(cx.waker().clone());
+                        Poll::Pending
+                    }
+                    Slot::Cancelled => unreachable!("slot cancelled while still being polled"),
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        let slot = match self.slot.take() {
+            Some(slot) => slot,
+            None => return,
+        };
+
+        let granted = {
+            let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+            let granted = matches!(*slot, Slot::Granted);
+            *slot = Slot::Cancelled;
+            granted
+        };
+
+        // The slot was handed over, but nobody is left to use it
+        if granted {
+            self.scheduler.release();
+        }
+    }
+}
+
+pub(crate) struct Permit {
+    scheduler: Arc<Scheduler>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
@@ -0,0 +1,134 @@
+//! A local tile proxy server
+//!
+//! Serves tiles at `/{tileset}/{z}/{x}/{y}.{ext}`, where `tileset` is the Maptiler Cloud endpoint
+//! of a built-in tileset, ex: `/satellite/2/1/1.jpg`. Tiles are fetched from Maptiler Cloud using
+//! the API key of the session, so browser map clients pointed at this server never see the key.
+//!
+//! # Example
+//!
+//! ```no_run
+//! #[tokio::main]
+//! async fn main() {
+//!     let maptiler = maptiler_cloud::Maptiler::new("placeholder api key").unwrap();
+//!
+//!     let proxy = maptiler_cloud::server::TileProxy::new(maptiler);
+//!
+//!     proxy.serve(([127, 0, 0, 1], 8080).into()).await.unwrap();
+//! }
+//! ```
+
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+
+use crate::{errors, Maptiler, TileRequest, TileSet};
+
+/// A handler that answers tile requests by fetching them from Maptiler Cloud
+#[derive(Clone)]
+pub struct TileProxy {
+    maptiler: Arc<Maptiler>,
+}
+
+impl TileProxy {
+    /// Creates a new TileProxy that fetches tiles using the given Maptiler session
+    pub fn new(maptiler: Maptiler) -> Self {
+        Self {
+            maptiler: Arc::new(maptiler),
+        }
+    }
+
+    /// Binds to the given address and serves tiles until the server fails
+    ///
+    /// Fails right away if the address can't be bound, ex: if the port is already in use
+    ///
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), hyper::Error> {
+        let make_service = make_service_fn(move |_connection| {
+            let proxy = self.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let proxy = proxy.clone();
+
+                    async move { Ok::<_, Infallible>(proxy.handle(request).await) }
+                }))
+            }
+        });
+
+        Server::try_bind(&addr)?.serve(make_service).await
+    }
+
+    /// Handles a single request
+    ///
+    /// This can be used to mount the proxy inside of an existing hyper or axum application
+    /// instead of using [`TileProxy::serve`]
+    ///
+    pub async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if request.method() != Method::GET {
+            return status_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        let tile_request = match parse_path(request.uri().path()) {
+            Some(Ok(tile_request)) => tile_request,
+            Some(Err(_)) => return status_response(StatusCode::BAD_REQUEST),
+            None => return status_response(StatusCode::NOT_FOUND),
+        };
+
+        match self.maptiler.create_request(tile_request).execute().await {
+            Ok(bytes) => Response::builder()
+                .header(header::CONTENT_TYPE, content_type(tile_request.set))
+                .body(Body::from(bytes))
+                .unwrap_or_else(|_| status_response(StatusCode::INTERNAL_SERVER_ERROR)),
+            // Forward the status from Maptiler Cloud, ex: 404 for a tile that doesn't exist
+            Err(errors::Error::Http(status)) => status_response(status),
+            Err(_) => status_response(StatusCode::BAD_GATEWAY),
+        }
+    }
+}
+
+// Parses /{tileset}/{z}/{x}/{y}.{ext}
+//
+// Returns None if the path doesn't name a tile at all, and an error if it names a tile that is
+// out of range
+fn parse_path(path: &str) -> Option<Result<TileRequest, errors::ArgumentError>> {
+    let mut segments = path.trim_start_matches('/').split('/');
+
+    let set = TileSet::from_endpoint(segments.next()?)?;
+    let zoom = segments.next()?.parse::<u32>().ok()?;
+    let x = segments.next()?.parse::<u32>().ok()?;
+    let (y, extension) = split_extension(segments.next()?)?;
+    let y = y.parse::<u32>().ok()?;
+
+    if segments.next().is_some() || extension != set.file_extension() {
+        return None;
+    }
+
+    Some(TileRequest::new(set, x, y, zoom))
+}
+
+// Splits "1.quantized-mesh-1.0" into ("1", "quantized-mesh-1.0"), since the extension may contain
+// dots itself
+fn split_extension(file_name: &str) -> Option<(&str, &str)> {
+    let dot = file_name.find('.')?;
+
+    Some((&file_name[..dot], &file_name[dot + 1..]))
+}
+
+fn content_type(set: TileSet) -> &'static str {
+    match set.file_extension() {
+        "jpg" => "image/jpeg",
+        "png" => "image/png",
+        "pbf" => "application/x-protobuf",
+        "quantized-mesh-1.0" => "application/vnd.quantized-mesh",
+        _ => "application/octet-stream",
+    }
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
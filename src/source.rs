@@ -0,0 +1,58 @@
+//! A generic interface for anything that can provide map tiles
+//!
+//! Map widgets and game engines can be written against [`TileSource`] instead of this crate's
+//! request types, so that they can be given tiles from Maptiler Cloud or anywhere else.
+
+use std::future::Future;
+
+use crate::{errors, Maptiler, TileRequest, TileSet};
+
+/// A source of map tiles in the [Tiled Web Map format](https://en.wikipedia.org/wiki/Tiled_web_map)
+pub trait TileSource {
+    /// The error returned when a tile can't be provided
+    type Error;
+
+    /// Returns the raw bytes of the tile at the given zoom level and coordinates
+    fn get_tile(
+        &self,
+        zoom: u32,
+        x: u32,
+        y: u32,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
+}
+
+/// A [`TileSource`] that requests tiles of a single tileset from Maptiler Cloud
+///
+/// This is created using [`Maptiler::tile_source`]
+#[derive(Clone)]
+pub struct MaptilerTileSource {
+    maptiler: Maptiler,
+    set: TileSet,
+}
+
+impl MaptilerTileSource {
+    pub(crate) fn new(maptiler: Maptiler, set: TileSet) -> Self {
+        Self { maptiler, set }
+    }
+
+    /// Returns the tileset that tiles are requested from
+    pub fn set(&self) -> TileSet {
+        self.set
+    }
+}
+
+impl TileSource for MaptilerTileSource {
+    type Error = errors::Error;
+
+    fn get_tile(
+        &self,
+        zoom: u32,
+        x: u32,
+        y: u32,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send {
+        let constructed = TileRequest::new(self.set, x, y, zoom)
+            .map(|tile_request| self.maptiler.create_request(tile_request));
+
+        async move { constructed?.execute().await }
+    }
+}
@@ -0,0 +1,154 @@
+//! Rewriting map styles to load everything from a local server instead of Maptiler Cloud
+//!
+//! The style JSON of a Maptiler Cloud map points MapLibre at the API for its tiles, fonts, and
+//! sprites, with the API key in every URL. [`localize`] points all of them at a local base URL
+//! instead, and strips the key:
+//!
+//! * Sources of built-in tilesets are pointed at the tiles of a
+//!   [`TileProxy`](crate::server::TileProxy) at `{local}/{tileset}/{z}/{x}/{y}.{ext}`
+//! * Every other Maptiler Cloud URL keeps its path, ex: the glyphs go to
+//!   `{local}/fonts/{fontstack}/{range}.pbf`, so an exported directory with the same layout can
+//!   be served in its place
+//!
+//! URLs that don't point at Maptiler Cloud are left alone.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), maptiler_cloud::errors::Error> {
+//! use maptiler_cloud::{Maptiler, StyleId};
+//!
+//! let maptiler = Maptiler::new("placeholder api key")?;
+//! let style_id = StyleId::new("streets-v2")?;
+//!
+//! let style = maptiler
+//!     .fetch_local_style(&style_id, "http://localhost:8080")
+//!     .await?;
+//!
+//! std::fs::write("style.json", style.to_string())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde_json::Value;
+
+use crate::TileSet;
+
+/// Points every URL of a style JSON that starts with `remote_base_url` at `local_base_url`
+/// instead, removing its API key, see the [module documentation](self)
+///
+/// The base URLs shouldn't end with a '/', ex: "https://api.maptiler.com"
+///
+pub fn localize(style: &mut Value, remote_base_url: &str, local_base_url: &str) {
+    let rewrite = |url: &mut Value| rewrite_url(url, remote_base_url, local_base_url);
+
+    if let Some(sources) = style.get_mut("sources").and_then(Value::as_object_mut) {
+        for source in sources.values_mut() {
+            localize_source(source, remote_base_url, local_base_url);
+        }
+    }
+
+    if let Some(glyphs) = style.get_mut("glyphs") {
+        rewrite(glyphs);
+    }
+
+    // Either a single URL, or a list of sprites with ids
+    match style.get_mut("sprite") {
+        Some(Value::Array(sprites)) => {
+            for sprite in sprites {
+                if let Some(url) = sprite.get_mut("url") {
+                    rewrite(url);
+                }
+            }
+        }
+        Some(sprite) => rewrite(sprite),
+        None => {}
+    }
+}
+
+fn localize_source(source: &mut Value, remote_base_url: &str, local_base_url: &str) {
+    let source = match source.as_object_mut() {
+        Some(source) => source,
+        None => return,
+    };
+
+    // A TileJSON URL of a built-in tileset, ex: /tiles/v3/tiles.json, is replaced by the tiles of
+    // the proxy, since it doesn't serve TileJSON. MapLibre needs the zoom range that the TileJSON
+    // would have given it, or it would ask for tiles that don't exist.
+    let set = source
+        .get("url")
+        .and_then(Value::as_str)
+        .and_then(|url| url.strip_prefix(remote_base_url))
+        .and_then(|path| path.strip_prefix("/tiles/"))
+        .and_then(|path| path.split('?').next())
+        .and_then(|path| path.strip_suffix("/tiles.json"))
+        .and_then(TileSet::from_endpoint);
+
+    if let Some(set) = set {
+        source.remove("url");
+        source.insert(
+            String::from("tiles"),
+            Value::from(vec![format!(
+                "{}/{}/{{z}}/{{x}}/{{y}}.{}",
+                local_base_url,
+                set.endpoint(),
+                set.file_extension()
+            )]),
+        );
+        source
+            .entry("minzoom")
+            .or_insert_with(|| Value::from(set.min_zoom()));
+        source
+            .entry("maxzoom")
+            .or_insert_with(|| Value::from(set.max_zoom()));
+    }
+
+    if let Some(url) = source.get_mut("url") {
+        rewrite_url(url, remote_base_url, local_base_url);
+    }
+
+    if let Some(Value::Array(tiles)) = source.get_mut("tiles") {
+        for url in tiles {
+            rewrite_url(url, remote_base_url, local_base_url);
+        }
+    }
+}
+
+fn rewrite_url(url: &mut Value, remote_base_url: &str, local_base_url: &str) {
+    if let Some(local) = url
+        .as_str()
+        .and_then(|url| localize_url(url, remote_base_url, local_base_url))
+    {
+        *url = Value::String(local);
+    }
+}
+
+// Swaps the base of a Maptiler Cloud URL, and removes the key from its query string
+fn localize_url(url: &str, remote_base_url: &str, local_base_url: &str) -> Option<String> {
+    let rest = url.strip_prefix(remote_base_url)?;
+
+    // Only whole path segments, so https://api.maptiler.com.example.com doesn't match
+    if !(rest.is_empty() || rest.starts_with('/') || rest.starts_with('?')) {
+        return None;
+    }
+
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    };
+
+    let query = query
+        .map(|query| {
+            query
+                .split('&')
+                .filter(|pair| *pair != "key" && !pair.starts_with("key="))
+                .collect::<Vec<_>>()
+                .join("&")
+        })
+        .filter(|query| !query.is_empty());
+
+    Some(match query {
+        Some(query) => format!("{}{}?{}", local_base_url, path, query),
+        None => format!("{}{}", local_base_url, path),
+    })
+}
@@ -0,0 +1,279 @@
+use std::{sync::OnceLock, time::Instant};
+
+use opentelemetry::{
+    global,
+    metrics::Histogram,
+    propagation::Injector,
+    trace::{SpanKind, Status, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+
+use crate::backend::{BackendError, HttpRequest, HttpResponse};
+
+const INSTRUMENTATION_NAME: &str = "maptiler-cloud";
+
+// The query parameter that holds the API key, which must never be exported
+const KEY_PARAMETER: &str = "key";
+
+// A span and duration measurement for one HTTP request, following the OpenTelemetry semantic
+// conventions for HTTP clients
+//
+// The duration covers the time until the response headers arrive, not the time spent downloading
+// the body.
+pub(crate) struct RequestTelemetry {
+    // Holds the span of the request
+    context: Context,
+    start: Instant,
+    attributes: Vec<KeyValue>,
+}
+
+impl RequestTelemetry {
+    // Starts the span of a request, and adds its trace context to the headers of the request with
+    // the global propagator, ex: as a W3C traceparent header, so that the request joins the trace
+    // of the caller
+    pub(crate) fn start(mut request: HttpRequest) -> (HttpRequest, Self) {
+        let method = &request.method;
+        let url = request.url.as_str();
+        let mut attributes = vec![KeyValue::new("http.request.method", method.to_string())];
+
+        if let Ok(url) = url::Url::parse(url) {
+            if let Some(host) = url.host_str() {
+                attributes.push(KeyValue::new("server.address", host.to_string()));
+            }
+
+            if let Some(port) = url.port_or_known_default() {
+                attributes.push(KeyValue::new("server.port", i64::from(port)));
+            }
+        }
+
+        let tracer = global::tracer(INSTRUMENTATION_NAME);
+
+        let span = tracer
+            .span_builder(method.to_string())
+            .with_kind(SpanKind::Client)
+            .with_attributes(
+                attributes
+                    .iter()
+                    .cloned()
+                    .chain(Some(KeyValue::new("url.full", redact(url)))),
+            )
+            .start(&tracer);
+        let context = Context::current_with_span(span);
+
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut HeaderInjector(&mut request.headers))
+        });
+
+        let telemetry = Self {
+            context,
+            start: Instant::now(),
+            attributes,
+        };
+
+        (request, telemetry)
+    }
+
+    pub(crate) fn finish(mut self, result: &Result<HttpResponse, BackendError>) {
+        let span = self.context.span();
+
+        match result {
+            Ok(res) => {
+                let status =
+                    KeyValue::new("http.response.status_code", i64::from(res.status.as_u16()));
+                span.set_attribute(status.clone());
+                self.attributes.push(status);
+
+                if res.status.is_client_error() || res.status.is_server_error() {
+                    let error = KeyValue::new("error.type", res.status.as_u16().to_string());
+                    span.set_attribute(error.clone());
+                    span.set_status(Status::error(res.status.to_string()));
+                    self.attributes.push(error);
+                }
+            }
+            Err(e) => {
+                let error = KeyValue::new("error.type", error_type(e));
+                span.set_attribute(error.clone());
+                // The error message may contain the URL, and with it the API key
+                span.set_status(Status::error(error_type(e)));
+                self.attributes.push(error);
+            }
+        }
+
+        request_duration().record(self.start.elapsed().as_secs_f64(), &self.attributes);
+        span.end();
+    }
+}
+
+fn request_duration() -> &'static Histogram<f64> {
+    static REQUEST_DURATION: OnceLock<Histogram<f64>> = OnceLock::new();
+
+    REQUEST_DURATION.get_or_init(|| {
+        global::meter(INSTRUMENTATION_NAME)
+            .f64_histogram("http.client.request.duration")
+            .with_description("Duration of HTTP client requests")
+            .with_unit("s")
+            .build()
+    })
+}
+
+fn error_type(e: &BackendError) -> &'static str {
+    if e.is_timeout() {
+        "timeout"
+    } else if e.is_connect() {
+        "connect"
+    } else {
+        "request"
+    }
+}
+
+// Writes the fields of a propagator into the headers of a request
+struct HeaderInjector<'a>(&'a mut http::HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            http::header::HeaderName::from_bytes(key.as_bytes()),
+            http::HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+// Replaces the API key in a URL so that it can be exported
+fn redact(url: &str) -> String {
+    let mut parsed = match url::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return String::from("REDACTED"),
+    };
+
+    // Rewriting the query would re-encode it, so URLs without a key are left exactly as they were
+    if !parsed.query_pairs().any(|(name, _)| name == KEY_PARAMETER) {
+        return String::from(url);
+    }
+
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(name, value)| {
+            if name == KEY_PARAMETER {
+                (name.into_owned(), String::from("REDACTED"))
+            } else {
+                (name.into_owned(), value.into_owned())
+            }
+        })
+        .collect();
+
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+
+    parsed.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::{
+        global,
+        propagation::{text_map_propagator::FieldIter, Extractor, Injector, TextMapPropagator},
+        trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+        Context,
+    };
+
+    use super::{redact, RequestTelemetry};
+    use crate::backend::{BackendError, BackendErrorKind, HttpRequest};
+
+    // Writes W3C traceparent headers, like the propagator of the OpenTelemetry SDK
+    #[derive(Debug)]
+    struct TraceParent;
+
+    impl TextMapPropagator for TraceParent {
+        fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+            let span = cx.span();
+            let span_context = span.span_context();
+
+            if span_context.is_valid() {
+                injector.set(
+                    "traceparent",
+                    format!(
+                        "00-{}-{}-{:02x}",
+                        span_context.trace_id(),
+                        span_context.span_id(),
+                        span_context.trace_flags().to_u8()
+                    ),
+                );
+            }
+        }
+
+        fn extract_with_context(&self, cx: &Context, _extractor: &dyn Extractor) -> Context {
+            cx.clone()
+        }
+
+        fn fields(&self) -> FieldIter<'_> {
+            FieldIter::new(&[])
+        }
+    }
+
+    #[test]
+    fn injects_trace_context() {
+        global::set_text_map_propagator(TraceParent);
+
+        // A request made while handling a traced request of the caller
+        let trace_id = TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap();
+        let parent = SpanContext::new(
+            trace_id,
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let _guard = Context::current().with_remote_span_context(parent).attach();
+
+        let (request, telemetry) = RequestTelemetry::start(HttpRequest {
+            method: http::Method::GET,
+            url: String::from("https://api.maptiler.com/tiles/satellite/0/0/0.jpg?key=SECRET"),
+            headers: http::HeaderMap::new(),
+        });
+
+        let traceparent = request.headers["traceparent"].to_str().unwrap();
+        assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+        assert!(traceparent.ends_with("-01"));
+
+        telemetry.finish(&Err(BackendError::new(
+            BackendErrorKind::Connect,
+            "unreachable",
+        )));
+    }
+
+    #[test]
+    fn redacts_key() {
+        assert_eq!(
+            redact("https://api.maptiler.com/tiles/satellite/2/1/1.jpg?key=SECRET"),
+            "https://api.maptiler.com/tiles/satellite/2/1/1.jpg?key=REDACTED"
+        );
+    }
+
+    #[test]
+    fn keeps_path_and_other_parameters() {
+        assert_eq!(
+            redact("https://api.maptiler.com/maps/streets-v2/style.json?mtsid=abc&key=SECRET&v=2"),
+            "https://api.maptiler.com/maps/streets-v2/style.json?mtsid=abc&key=REDACTED&v=2"
+        );
+    }
+
+    #[test]
+    fn leaves_urls_without_key_alone() {
+        for url in [
+            "https://api.maptiler.com/tiles/satellite/tiles.json",
+            "https://api.maptiler.com/fonts/Noto%20Sans/0-255.pbf?v=1%202",
+        ] {
+            assert_eq!(redact(url), url);
+        }
+
+        // Only a parameter named exactly "key" is the API key
+        let url = "https://example.com/tiles/0/0/0.png?monkey=1&keys=2";
+        assert_eq!(redact(url), url);
+    }
+
+    #[test]
+    fn redacts_unparseable_urls() {
+        assert_eq!(redact("not a url?key=SECRET"), "REDACTED");
+    }
+}
@@ -0,0 +1,138 @@
+//! Decoding the pixels of [`TileSet::TerrainRGB`](crate::TileSet::TerrainRGB) tiles into
+//! elevations
+//!
+//! TerrainRGB tiles are PNGs whose pixels each hold an elevation in meters, as
+//! `-10000 + (R * 256 * 256 + G * 256 + B) * 0.1`. This crate doesn't decode PNGs, so these
+//! functions work on the raw pixels from an image decoder, ex: the `png` or `image` crates.
+//!
+//! On x86 and x86_64 CPUs with SSSE3, four pixels are decoded at a time with SIMD instructions,
+//! and everywhere else a pixel at a time. Both give exactly the same elevations.
+//!
+//! # Example
+//!
+//! ```
+//! use maptiler_cloud::terrain;
+//!
+//! // Two pixels, decoded from a TerrainRGB PNG
+//! let pixels = [1, 134, 160, 1, 137, 48];
+//!
+//! let mut elevations = [0.0; 2];
+//! terrain::decode_rgb(&pixels, &mut elevations);
+//!
+//! assert_eq!(elevations[0], 0.0);
+//! assert!((elevations[1] - 65.6).abs() < 0.01);
+//! ```
+
+// The elevation of the pixel (0, 0, 0) in meters, and the meters between two encoded values
+const OFFSET: f32 = -10000.0;
+const SCALE: f32 = 0.1;
+
+/// Decodes the elevation in meters of a single TerrainRGB pixel
+pub fn elevation(r: u8, g: u8, b: u8) -> f32 {
+    let encoded = u32::from(r) << 16 | u32::from(g) << 8 | u32::from(b);
+
+    // Every encoded value is below 2^24, so it converts to f32 exactly
+    encoded as f32 * SCALE + OFFSET
+}
+
+/// Decodes tightly packed RGB pixels, three bytes each, into one elevation per pixel
+///
+/// # Panics
+///
+/// Panics if `pixels` doesn't hold exactly three bytes for every elevation
+///
+pub fn decode_rgb(pixels: &[u8], elevations: &mut [f32]) {
+    decode(pixels, elevations, 3);
+}
+
+/// Decodes tightly packed RGBA pixels, four bytes each, into one elevation per pixel
+///
+/// The alpha channel is ignored.
+///
+/// # Panics
+///
+/// Panics if `pixels` doesn't hold exactly four bytes for every elevation
+///
+pub fn decode_rgba(pixels: &[u8], elevations: &mut [f32]) {
+    decode(pixels, elevations, 4);
+}
+
+fn decode(pixels: &[u8], elevations: &mut [f32], channels: usize) {
+    assert_eq!(
+        pixels.len(),
+        elevations.len() * channels,
+        "{} bytes of pixels can't be decoded into {} elevations",
+        pixels.len(),
+        elevations.len()
+    );
+
+    let mut decoded = 0;
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("ssse3") {
+            // Safety: the CPU has just been checked to support SSSE3
+            decoded = unsafe { x86::decode(pixels, elevations, channels) };
+        }
+    }
+
+    decode_scalar(
+        &pixels[decoded * channels..],
+        &mut elevations[decoded..],
+        channels,
+    );
+}
+
+fn decode_scalar(pixels: &[u8], elevations: &mut [f32], channels: usize) {
+    for (pixel, elevation_out) in pixels.chunks_exact(channels).zip(elevations) {
+        *elevation_out = elevation(pixel[0], pixel[1], pixel[2]);
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    use super::{OFFSET, SCALE};
+
+    // Moves the R, G, and B bytes of four pixels into the lowest three bytes of four u32s, most
+    // significant first, and zeroes the top byte. -1 sets the high bit, which zeroes a byte.
+    const RGB_SHUFFLE: [i8; 16] = [2, 1, 0, -1, 5, 4, 3, -1, 8, 7, 6, -1, 11, 10, 9, -1];
+    const RGBA_SHUFFLE: [i8; 16] = [2, 1, 0, -1, 6, 5, 4, -1, 10, 9, 8, -1, 14, 13, 12, -1];
+
+    // Decodes four pixels at a time for as long as 16 bytes can be loaded, and returns how many
+    // pixels were decoded. The rest are left for the scalar loop.
+    //
+    // Safety: the CPU must support SSSE3
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn decode(pixels: &[u8], elevations: &mut [f32], channels: usize) -> usize {
+        let shuffle = if channels == 3 {
+            RGB_SHUFFLE
+        } else {
+            RGBA_SHUFFLE
+        };
+        let shuffle = _mm_loadu_si128(shuffle.as_ptr() as *const __m128i);
+        let scale = _mm_set1_ps(SCALE);
+        let offset = _mm_set1_ps(OFFSET);
+
+        let mut decoded = 0;
+
+        // Four RGB pixels are only 12 bytes, but a load always reads 16
+        while decoded * channels + 16 <= pixels.len() {
+            let bytes = _mm_loadu_si128(pixels.as_ptr().add(decoded * channels) as *const __m128i);
+            let encoded = _mm_shuffle_epi8(bytes, shuffle);
+
+            // Multiplying and then adding, rather than a fused multiply-add, rounds exactly like
+            // the scalar loop does
+            let meters = _mm_add_ps(_mm_mul_ps(_mm_cvtepi32_ps(encoded), scale), offset);
+            _mm_storeu_ps(elevations.as_mut_ptr().add(decoded), meters);
+
+            decoded += 4;
+        }
+
+        decoded
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::backend::HttpBackend;
+
+// A token bucket that limits how many bytes per second may be downloaded, shared by every request
+// of a session
+//
+// Downloads take their bytes out of the bucket as chunks arrive. The bucket may go into debt by
+// one chunk, and whoever caused the debt sleeps until it is paid back, so the average rate never
+// exceeds the limit.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    bytes_per_second: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    available: f64,
+    last_refill: Instant,
+}
+
+impl Throttle {
+    pub(crate) fn new(bytes_per_second: u64) -> Self {
+        // A bucket that can hold zero bytes would never let anything through
+        let bytes_per_second = bytes_per_second.max(1) as f64;
+
+        Self {
+            bytes_per_second,
+            state: Mutex::new(BucketState {
+                available: bytes_per_second,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    // Takes the given number of bytes out of the bucket, sleeping if that puts the bucket in debt
+    pub(crate) async fn consume(&self, backend: &dyn HttpBackend, bytes: usize) {
+        let debt = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+            // Refill for the time since the last chunk, holding at most one second of bytes
+            let now = Instant::now();
+            let refill =
+                now.duration_since(state.last_refill).as_secs_f64() * self.bytes_per_second;
+            state.available = (state.available + refill).min(self.bytes_per_second);
+            state.last_refill = now;
+
+            state.available -= bytes as f64;
+
+            -state.available
+        };
+
+        if debt > 0.0 {
+            backend
+                .sleep(Duration::from_secs_f64(debt / self.bytes_per_second))
+                .await;
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::TileSet;
+
+macro_rules! tile_wrappers {
+    ($($(#[$doc:meta])* $name:ident;)*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct $name(Vec<u8>);
+
+            impl $name {
+                /// Returns the raw bytes of the tile
+                pub fn as_bytes(&self) -> &[u8] {
+                    &self.0
+                }
+
+                /// Returns the raw bytes of the tile, consuming it
+                pub fn into_bytes(self) -> Vec<u8> {
+                    self.0
+                }
+            }
+
+            impl AsRef<[u8]> for $name {
+                fn as_ref(&self) -> &[u8] {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+tile_wrappers! {
+    /// A JPEG satellite image, from [`TileSet::Satellite`] and the medium resolution satellite
+    /// tilesets
+    SatelliteTile;
+    /// A PNG whose pixels encode elevations, from [`TileSet::TerrainRGB`]
+    ///
+    /// Once decoded by an image decoder, the pixels can be turned into elevations with
+    /// [`terrain::decode_rgb`](crate::terrain::decode_rgb)
+    ///
+    TerrainRgbTile;
+    /// A transparent PNG of shaded relief, from [`TileSet::Hillshading`]
+    HillshadeTile;
+    /// A Mapbox Vector Tile in protobuf format, from the vector tilesets such as
+    /// [`TileSet::MaptilerPlanet`]
+    VectorTile;
+    /// A quantized mesh of terrain triangles, from [`TileSet::Terrain3D`]
+    QuantizedMeshTile;
+}
+
+/// A downloaded tile, typed by the file format of the tileset it came from
+///
+/// Matching on a Tile makes it impossible to mix up formats by accident, such as passing a vector
+/// tile to a JPEG decoder.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> Result<(), maptiler_cloud::errors::Error> {
+/// use maptiler_cloud::{Maptiler, Tile, TileRequest, TileSet};
+///
+/// let maptiler = Maptiler::new("placeholder api key")?;
+/// let tile_request = TileRequest::new(TileSet::Satellite, 2, 1, 2)?;
+///
+/// match maptiler.fetch_tile(tile_request).await? {
+///     Tile::Satellite(jpg) => { /* decode jpg.as_bytes() as a JPEG */ }
+///     _ => unreachable!(),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tile {
+    /// A tile from one of the satellite tilesets
+    Satellite(SatelliteTile),
+    /// A tile from [`TileSet::TerrainRGB`]
+    TerrainRgb(TerrainRgbTile),
+    /// A tile from [`TileSet::Hillshading`]
+    Hillshade(HillshadeTile),
+    /// A tile from one of the vector tilesets
+    Vector(VectorTile),
+    /// A tile from [`TileSet::Terrain3D`]
+    QuantizedMesh(QuantizedMeshTile),
+    /// A tile from a [`TileSet::Custom`] tileset, whose format is only known by its extension
+    Custom {
+        /// The file extension of the tileset, ex: "png"
+        extension: &'static str,
+        /// The raw bytes of the tile
+        bytes: Vec<u8>,
+    },
+}
+
+impl Tile {
+    /// Wraps the raw bytes of a tile from the given tileset
+    ///
+    /// This doesn't check that the bytes are actually in the format of the tileset, which
+    /// [`Maptiler::fetch_tile`](crate::Maptiler::fetch_tile) already does for downloaded tiles.
+    ///
+    pub fn new(set: TileSet, bytes: Vec<u8>) -> Self {
+        match set {
+            TileSet::Contours
+            | TileSet::Countries
+            | TileSet::Land
+            | TileSet::Landcover
+            | TileSet::MaptilerPlanet
+            | TileSet::MaptilerPlanetLite
+            | TileSet::OpenMapTiles
+            | TileSet::OpenMapTilesWGS84
+            | TileSet::Outdoor => Tile::Vector(VectorTile(bytes)),
+            TileSet::Hillshading => Tile::Hillshade(HillshadeTile(bytes)),
+            TileSet::TerrainRGB => Tile::TerrainRgb(TerrainRgbTile(bytes)),
+            TileSet::Satellite
+            | TileSet::SatelliteMediumRes2016
+            | TileSet::SatelliteMediumRes2018 => Tile::Satellite(SatelliteTile(bytes)),
+            TileSet::Terrain3D => Tile::QuantizedMesh(QuantizedMeshTile(bytes)),
+            TileSet::Custom {
+                endpoint: _,
+                extension,
+            } => Tile::Custom { extension, bytes },
+        }
+    }
+
+    /// Returns the raw bytes of the tile
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Tile::Satellite(tile) => tile.as_bytes(),
+            Tile::TerrainRgb(tile) => tile.as_bytes(),
+            Tile::Hillshade(tile) => tile.as_bytes(),
+            Tile::Vector(tile) => tile.as_bytes(),
+            Tile::QuantizedMesh(tile) => tile.as_bytes(),
+            Tile::Custom { bytes, .. } => bytes,
+        }
+    }
+
+    /// Returns the raw bytes of the tile, consuming it
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Tile::Satellite(tile) => tile.into_bytes(),
+            Tile::TerrainRgb(tile) => tile.into_bytes(),
+            Tile::Hillshade(tile) => tile.into_bytes(),
+            Tile::Vector(tile) => tile.into_bytes(),
+            Tile::QuantizedMesh(tile) => tile.into_bytes(),
+            Tile::Custom { bytes, .. } => bytes,
+        }
+    }
+}
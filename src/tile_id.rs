@@ -0,0 +1,107 @@
+use crate::{errors, TileRequest, TileSet, TileX, TileY, Zoom};
+
+// The zoom level is stored in the top 6 bits, and the interleaved x and y in the lower 58 bits
+const ZOOM_SHIFT: u32 = 58;
+const COORDINATE_BITS: u32 = ZOOM_SHIFT / 2;
+
+/// The zoom level and coordinates of a tile, packed into a single u64
+///
+/// The zoom level is stored in the top bits, and the x and y coordinates are interleaved below it
+/// in [Z-order](https://en.wikipedia.org/wiki/Z-order_curve). Sorting TileIds groups tiles by
+/// zoom level, and then keeps tiles that are close together on the map close together in the
+/// order. This makes them good keys for databases and key-value stores.
+///
+/// A TileId doesn't know which tileset it is from
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TileId(u64);
+
+impl TileId {
+    /// The highest zoom level that a TileId can hold
+    pub const MAX_ZOOM: u32 = COORDINATE_BITS;
+
+    /// Creates a new TileId, if the zoom level is at most [`TileId::MAX_ZOOM`], and x and y are
+    /// less than 2^zoom
+    pub fn new(zoom: impl Into<Zoom>, x: impl Into<TileX>, y: impl Into<TileY>) -> Option<Self> {
+        let (zoom, x, y) = (zoom.into().get(), x.into().get(), y.into().get());
+
+        if zoom > Self::MAX_ZOOM || u64::from(x) >> zoom != 0 || u64::from(y) >> zoom != 0 {
+            return None;
+        }
+
+        Some(Self(
+            u64::from(zoom) << ZOOM_SHIFT | spread(x) | spread(y) << 1,
+        ))
+    }
+
+    /// Reads a TileId back from the value returned by [`TileId::as_u64`], if it is valid
+    pub fn from_u64(id: u64) -> Option<Self> {
+        let tile_id = Self(id);
+
+        // Garbage bits would otherwise decode to a different, valid tile
+        Self::new(tile_id.zoom(), tile_id.x(), tile_id.y()).filter(|decoded| *decoded == tile_id)
+    }
+
+    /// Returns the packed u64
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the zoom level of the tile
+    pub fn zoom(&self) -> u32 {
+        (self.0 >> ZOOM_SHIFT) as u32
+    }
+
+    /// Returns the x coordinate of the tile
+    pub fn x(&self) -> u32 {
+        compact(self.0)
+    }
+
+    /// Returns the y coordinate of the tile
+    pub fn y(&self) -> u32 {
+        compact(self.0 >> 1)
+    }
+
+    /// Creates a [`TileRequest`] for this tile from the given tileset, checking it against the
+    /// tileset's zoom range
+    pub fn tile_request(&self, set: TileSet) -> Result<TileRequest, errors::ArgumentError> {
+        TileRequest::new(set, self.x(), self.y(), self.zoom())
+    }
+}
+
+impl TileRequest {
+    /// Returns the [`TileId`] of this tile
+    ///
+    /// This is None if the zoom level is above [`TileId::MAX_ZOOM`], or if x or y is 2^zoom,
+    /// which TileRequest allows but which is past the edge of the map
+    ///
+    pub fn tile_id(&self) -> Option<TileId> {
+        TileId::new(self.zoom(), self.x(), self.y())
+    }
+}
+
+// Spreads the bits of a coordinate out to every other bit, ex: 0b111 becomes 0b10101
+fn spread(coordinate: u32) -> u64 {
+    let mut bits = u64::from(coordinate);
+
+    bits = (bits | bits << 16) & 0x0000_FFFF_0000_FFFF;
+    bits = (bits | bits << 8) & 0x00FF_00FF_00FF_00FF;
+    bits = (bits | bits << 4) & 0x0F0F_0F0F_0F0F_0F0F;
+    bits = (bits | bits << 2) & 0x3333_3333_3333_3333;
+    bits = (bits | bits << 1) & 0x5555_5555_5555_5555;
+
+    bits
+}
+
+// The inverse of spread, which only reads the even bits below the zoom level
+fn compact(bits: u64) -> u32 {
+    let mut bits = bits & 0x5555_5555_5555_5555 & ((1 << ZOOM_SHIFT) - 1);
+
+    bits = (bits | bits >> 1) & 0x3333_3333_3333_3333;
+    bits = (bits | bits >> 2) & 0x0F0F_0F0F_0F0F_0F0F;
+    bits = (bits | bits >> 4) & 0x00FF_00FF_00FF_00FF;
+    bits = (bits | bits >> 8) & 0x0000_FFFF_0000_FFFF;
+    bits = (bits | bits >> 16) & 0x0000_0000_FFFF_FFFF;
+
+    bits as u32
+}
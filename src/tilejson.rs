@@ -0,0 +1,68 @@
+use serde::Deserialize;
+
+use crate::{projection, TileX, TileY, Zoom};
+
+/// The metadata that Maptiler Cloud publishes about a tileset in its `tiles.json` document
+///
+/// See the [TileJSON specification](https://github.com/mapbox/tilejson-spec) for the meaning of
+/// each field. Only the fields that are useful to this crate are kept.
+///
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TileJson {
+    /// The name of the tileset, ex: "Satellite"
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The attribution that must be displayed alongside the tiles
+    #[serde(default)]
+    pub attribution: Option<String>,
+    /// The minimum zoom level that the tileset currently supports
+    #[serde(default)]
+    pub minzoom: u32,
+    /// The maximum zoom level that the tileset currently supports
+    #[serde(default = "default_maxzoom")]
+    pub maxzoom: u32,
+    /// The area that the tileset covers as [west, south, east, north] in degrees
+    #[serde(default)]
+    pub bounds: Option<[f64; 4]>,
+    /// The file format of the tiles, ex: "jpg"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+// The TileJSON specification says that a missing maxzoom should be treated as 30
+fn default_maxzoom() -> u32 {
+    30
+}
+
+impl TileJson {
+    /// Parses a TileJSON document from its raw bytes
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    /// Returns true if the given tile overlaps the bounds of this tileset
+    ///
+    /// If the tileset does not report any bounds it is assumed to cover the whole world. Bounds
+    /// may cross the antimeridian, in which case west is greater than east.
+    ///
+    pub fn covers(&self, x: impl Into<TileX>, y: impl Into<TileY>, zoom: impl Into<Zoom>) -> bool {
+        let [west, south, east, north] = match self.bounds {
+            Some(bounds) => bounds,
+            None => return true,
+        };
+
+        let [tile_west, tile_south, tile_east, tile_north] = projection::tile_bounds(zoom, x, y);
+
+        let overlaps = |west: f64, east: f64| tile_west < east && tile_east > west;
+
+        // Bounds that cross the antimeridian have their west edge east of their east edge, and
+        // are made of the part up to 180 degrees east, and the part from 180 degrees west
+        let covers_longitude = if west <= east {
+            overlaps(west, east)
+        } else {
+            overlaps(west, 180.0) || overlaps(-180.0, east)
+        };
+
+        covers_longitude && tile_south < north && tile_north > south
+    }
+}
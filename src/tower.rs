@@ -0,0 +1,36 @@
+use std::task::{Context, Poll};
+
+use tower_service::Service;
+
+use crate::{
+    backend::BoxFuture, errors, Maptiler, RequestType, StyleRequest, TileJsonRequest, TileRequest,
+};
+
+// Lets standard tower middleware, such as retries, rate limits, and load shedding, be stacked in
+// front of a session, for every type of request
+macro_rules! impl_service {
+    ($request:ty) => {
+        impl Service<$request> for Maptiler {
+            type Response = Vec<u8>;
+            type Error = errors::Error;
+            type Future = BoxFuture<'static, Result<Vec<u8>, errors::Error>>;
+
+            // Sessions have no capacity of their own to wait for. Concurrency limits and quotas
+            // are waited on inside of the returned future.
+            fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, request: $request) -> Self::Future {
+                let constructed = self.create_request(request);
+
+                Box::pin(async move { constructed.execute().await })
+            }
+        }
+    };
+}
+
+impl_service!(RequestType);
+impl_service!(TileRequest);
+impl_service!(TileJsonRequest);
+impl_service!(StyleRequest);
@@ -0,0 +1,48 @@
+use crate::{errors, TileSet};
+
+const JPEG_START: &[u8] = &[0xFF, 0xD8, 0xFF];
+const JPEG_END: &[u8] = &[0xFF, 0xD9];
+const PNG_START: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+// The IEND chunk, which is always the last chunk of a PNG
+const PNG_END: &[u8] = &[0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82];
+const GZIP_START: &[u8] = &[0x1F, 0x8B];
+// The first field of a vector tile is always a layer: field 3, length-delimited
+const PBF_START: &[u8] = &[0x1A];
+// The fixed size header of a quantized mesh tile
+const QUANTIZED_MESH_HEADER_LEN: usize = 88;
+
+// Checks that a downloaded tile is complete and is actually the format that its tileset returns
+//
+// This catches tiles that were truncated by a dropped connection, and error pages that were served
+// in place of a tile
+pub(crate) fn verify_tile(
+    set: TileSet,
+    body: &[u8],
+    content_length: Option<u64>,
+) -> Result<(), errors::Error> {
+    if let Some(expected) = content_length {
+        if expected != body.len() as u64 {
+            return Err(errors::Error::TruncatedTile(expected, body.len() as u64));
+        }
+    }
+
+    let extension = set.file_extension();
+
+    let valid = match extension {
+        "jpg" => body.starts_with(JPEG_START) && body.ends_with(JPEG_END),
+        "png" => body.starts_with(PNG_START) && body.ends_with(PNG_END),
+        // Vector tiles may be empty if there is nothing in them, and may be served compressed
+        "pbf" => body.is_empty() || body.starts_with(PBF_START) || body.starts_with(GZIP_START),
+        "quantized-mesh-1.0" => {
+            body.starts_with(GZIP_START) || body.len() >= QUANTIZED_MESH_HEADER_LEN
+        }
+        // There is no way to know what a custom tileset should look like
+        _ => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(errors::Error::CorruptTile(extension))
+    }
+}
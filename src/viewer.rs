@@ -0,0 +1,190 @@
+//! A static HTML page for looking at raster tiles in a browser
+//!
+//! The page shows a single [Leaflet](https://leafletjs.com/) map of a tile URL template, such as
+//! a directory of tiles saved with
+//! [`ConstructedRequest::execute_to_file`](crate::ConstructedRequest::execute_to_file), or a
+//! [`TileProxy`](crate::server::TileProxy). It is zoomed to the bounds and zoom range of the
+//! tiles.
+//!
+//! The page is a single file, but Leaflet itself is loaded from unpkg by default, so the browser
+//! needs to be online for it. To view tiles offline, download `leaflet.js` and `leaflet.css`
+//! from a Leaflet release, and point [`Viewer::leaflet_url`] at them, ex: a `leaflet` directory
+//! next to the page.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use maptiler_cloud::viewer::Viewer;
+//!
+//! // Next to a satellite/{z}/{x}/{y}.jpg directory
+//! Viewer::new("satellite/{z}/{x}/{y}.jpg", 0, 12)
+//!     .bounds([5.9, 45.8, 10.5, 47.8])
+//!     .title("Switzerland")
+//!     .write("index.html")
+//!     .unwrap();
+//! ```
+
+use std::path::Path;
+
+use crate::{errors, file, projection::MAX_LATITUDE, TileJson};
+
+const LEAFLET_URL: &str = "https://unpkg.com/leaflet@1.9.4/dist";
+
+/// A static HTML viewer for a tile URL template, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewer {
+    tiles_url: String,
+    min_zoom: u32,
+    max_zoom: u32,
+    bounds: Option<[f64; 4]>,
+    title: String,
+    attribution: Option<String>,
+    leaflet_url: String,
+}
+
+impl Viewer {
+    /// Creates a new Viewer for tiles at the given URL template, which has {z}, {x}, and {y}
+    /// placeholders, and may be relative to the page
+    ///
+    /// The URL ends up in the page as is, so it shouldn't contain an API key unless the page is
+    /// kept private
+    ///
+    pub fn new<S>(tiles_url: S, min_zoom: u32, max_zoom: u32) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            tiles_url: tiles_url.into(),
+            min_zoom,
+            max_zoom,
+            bounds: None,
+            title: String::from("Tiles"),
+            attribution: None,
+            leaflet_url: String::from(LEAFLET_URL),
+        }
+    }
+
+    /// Creates a new Viewer with the zoom range, bounds, name, and attribution of a tileset
+    pub fn from_tilejson<S>(tiles_url: S, tilejson: &TileJson) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut viewer = Self::new(tiles_url, tilejson.minzoom, tilejson.maxzoom);
+        viewer.bounds = tilejson.bounds;
+        viewer.attribution = tilejson.attribution.clone();
+
+        if let Some(name) = &tilejson.name {
+            viewer.title = name.clone();
+        }
+
+        viewer
+    }
+
+    /// Sets the area that the map starts zoomed to, and can't be panned away from, as
+    /// `[west, south, east, north]` in degrees like in TileJSON
+    ///
+    /// Without bounds the whole world is shown
+    ///
+    pub fn bounds(mut self, bounds: [f64; 4]) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Sets the title of the page
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the attribution shown in the corner of the map, which may contain HTML
+    pub fn attribution<S: Into<String>>(mut self, attribution: S) -> Self {
+        self.attribution = Some(attribution.into());
+        self
+    }
+
+    /// Sets the URL of the directory that `leaflet.js` and `leaflet.css` are loaded from, which
+    /// may be relative to the page
+    ///
+    /// By default they are loaded from unpkg, ex: "https://unpkg.com/leaflet@1.9.4/dist"
+    ///
+    pub fn leaflet_url<S: Into<String>>(mut self, leaflet_url: S) -> Self {
+        self.leaflet_url = leaflet_url.into();
+        self
+    }
+
+    /// Returns the HTML of the page
+    pub fn to_html(&self) -> String {
+        let [west, south, east, north] =
+            self.bounds
+                .unwrap_or([-180.0, -MAX_LATITUDE, 180.0, MAX_LATITUDE]);
+
+        let fit = match self.bounds {
+            Some(_) => "map.fitBounds(bounds);\n      map.setMaxBounds(bounds.pad(0.5));",
+            None => "map.fitWorld();",
+        };
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{title}</title>
+    <link rel="stylesheet" href="{leaflet_url}/leaflet.css">
+    <script src="{leaflet_url}/leaflet.js"></script>
+    <style>
+      html, body, #map {{ height: 100%; margin: 0; }}
+    </style>
+  </head>
+  <body>
+    <div id="map"></div>
+    <script>
+      const bounds = L.latLngBounds([{south}, {west}], [{north}, {east}]);
+      const map = L.map("map", {{ minZoom: {min_zoom}, maxZoom: {max_zoom} }});
+      L.tileLayer({tiles_url}, {{
+        minZoom: {min_zoom},
+        maxZoom: {max_zoom},
+        bounds: bounds,
+        attribution: {attribution},
+      }}).addTo(map);
+      {fit}
+    </script>
+  </body>
+</html>
+"#,
+            title = escape_html(&self.title),
+            leaflet_url = escape_html(self.leaflet_url.trim_end_matches('/')),
+            south = south,
+            west = west,
+            north = north,
+            east = east,
+            min_zoom = self.min_zoom,
+            max_zoom = self.max_zoom,
+            tiles_url = js_string(&self.tiles_url),
+            attribution = js_string(self.attribution.as_deref().unwrap_or("")),
+            fit = fit,
+        )
+    }
+
+    /// Writes the page to a file, replacing it atomically if it exists
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), errors::Error> {
+        Ok(file::write_atomic(
+            path.as_ref(),
+            self.to_html().as_bytes(),
+        )?)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// A JavaScript string literal, which can't end the <script> element that it is in
+fn js_string(text: &str) -> String {
+    serde_json::Value::from(text)
+        .to_string()
+        .replace("</", "<\\/")
+}
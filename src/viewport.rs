@@ -0,0 +1,191 @@
+//! Working out which tiles cover the screen of a map widget, and which to prefetch around it
+//!
+//! # Example
+//!
+//! ```
+//! use maptiler_cloud::{viewport::Viewport, TileSet};
+//!
+//! let viewport = Viewport {
+//!     latitude: 46.5,
+//!     longitude: 7.9,
+//!     zoom: 10,
+//!     width: 800,
+//!     height: 600,
+//!     tile_size: 256,
+//! };
+//!
+//! for tile in viewport.tiles(TileSet::Satellite).unwrap() {
+//!     // Request tile.request, and draw it at (tile.offset_x, tile.offset_y)
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use crate::{
+    errors, projection, ConstructedRequest, Maptiler, Priority, TileId, TileRequest, TileSet,
+};
+
+/// A rectangular view of a Web Mercator map, centered on a point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// The latitude of the center of the view in degrees
+    pub latitude: f64,
+    /// The longitude of the center of the view in degrees
+    pub longitude: f64,
+    /// The zoom level of the view
+    pub zoom: u32,
+    /// The width of the view in pixels
+    pub width: u32,
+    /// The height of the view in pixels
+    pub height: u32,
+    /// The size that tiles are drawn at in pixels, usually 256 or 512
+    pub tile_size: u32,
+}
+
+/// A tile that is needed to fill a [`Viewport`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportTile {
+    /// The request for the tile
+    pub request: TileRequest,
+    /// Where the left edge of the tile goes, in pixels from the left edge of the view
+    ///
+    /// This is negative for tiles that are only partly on screen.
+    pub offset_x: f64,
+    /// Where the top edge of the tile goes, in pixels from the top edge of the view
+    pub offset_y: f64,
+}
+
+impl Viewport {
+    /// Returns every tile of the given tileset that is at least partly inside the view, row by row
+    /// from the top left
+    ///
+    /// Views that are wider than the world repeat it, like web maps do when zoomed out, so the
+    /// same tile may be returned more than once at different offsets. Nothing is returned above
+    /// or below the edges of the map.
+    ///
+    pub fn tiles(&self, set: TileSet) -> Result<Vec<ViewportTile>, errors::ArgumentError> {
+        let tiles_per_side = 1u64 << self.zoom.min(32);
+        let tile_size = f64::from(self.tile_size.max(1));
+        let world_size = tile_size * tiles_per_side as f64;
+
+        let (center_x, center_y) = projection::to_unit(self.longitude, self.latitude);
+        let left = center_x * world_size - f64::from(self.width) / 2.0;
+        let top = center_y * world_size - f64::from(self.height) / 2.0;
+
+        let first_column = (left / tile_size).floor() as i64;
+        let last_column = ((left + f64::from(self.width)) / tile_size).ceil() as i64;
+        let first_row = ((top / tile_size).floor() as i64).max(0);
+        let last_row =
+            (((top + f64::from(self.height)) / tile_size).ceil() as i64).min(tiles_per_side as i64);
+
+        let mut tiles = Vec::new();
+
+        for row in first_row..last_row {
+            for column in first_column..last_column {
+                // Columns past the antimeridian wrap around to the other side of the world
+                let x = column.rem_euclid(tiles_per_side as i64) as u32;
+
+                tiles.push(ViewportTile {
+                    request: TileRequest::new(set, x, row as u32, self.zoom)?,
+                    offset_x: column as f64 * tile_size - left,
+                    offset_y: row as f64 * tile_size - top,
+                });
+            }
+        }
+
+        Ok(tiles)
+    }
+}
+
+/// Keeps a ring of tiles around a moving [`Viewport`] requested ahead of time
+///
+/// Each time the camera moves, pass the new viewport to [`Prefetcher::update`], and execute the
+/// requests that it returns. Tiles on screen come back with [`Priority::High`], and the ring of
+/// tiles around the screen with [`Priority::Low`], closest first, so with a
+/// [`MaptilerBuilder::max_concurrent_requests`](crate::MaptilerBuilder::max_concurrent_requests)
+/// limit the visible tiles always go first. Only tiles that weren't already in view or in the
+/// ring at the last update are returned, so the caller is expected to keep the tiles it has
+/// downloaded until they leave the ring.
+///
+/// Use one Prefetcher per tileset, ex: one for [`TileSet::Satellite`] and one for
+/// [`TileSet::TerrainRGB`].
+///
+#[derive(Clone)]
+pub struct Prefetcher {
+    maptiler: Maptiler,
+    set: TileSet,
+    ring: u32,
+    requested: HashSet<TileId>,
+}
+
+impl Prefetcher {
+    /// Creates a new Prefetcher that keeps `ring` tiles on every side of the view requested
+    pub fn new(maptiler: Maptiler, set: TileSet, ring: u32) -> Self {
+        Self {
+            maptiler,
+            set,
+            ring,
+            requested: HashSet::new(),
+        }
+    }
+
+    /// Returns the tileset that tiles are requested from
+    pub fn set(&self) -> TileSet {
+        self.set
+    }
+
+    /// Moves the view, returning the requests for tiles that have come into view or into the ring
+    pub fn update(
+        &mut self,
+        viewport: &Viewport,
+    ) -> Result<Vec<ConstructedRequest>, errors::ArgumentError> {
+        let margin = self
+            .ring
+            .saturating_mul(viewport.tile_size)
+            .saturating_mul(2);
+        let surroundings = Viewport {
+            width: viewport.width.saturating_add(margin),
+            height: viewport.height.saturating_add(margin),
+            ..*viewport
+        };
+
+        let mut visible = viewport.tiles(self.set)?;
+        let mut ring = surroundings.tiles(self.set)?;
+
+        // Closest to the center first, which is also the center of the surroundings
+        let distance = |tile: &ViewportTile| {
+            let half_tile = f64::from(viewport.tile_size) / 2.0;
+            let x = tile.offset_x + half_tile - f64::from(surroundings.width) / 2.0;
+            let y = tile.offset_y + half_tile - f64::from(surroundings.height) / 2.0;
+
+            x * x + y * y
+        };
+        ring.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+
+        let mut current = HashSet::new();
+        let mut requests = Vec::new();
+
+        for (tiles, priority) in [(&mut visible, Priority::High), (&mut ring, Priority::Low)] {
+            for tile in tiles.iter() {
+                // Every tile returned by Viewport::tiles is on the map, so it always has an id
+                let tile_id = match tile.request.tile_id() {
+                    Some(tile_id) => tile_id,
+                    None => continue,
+                };
+
+                // Views wider than the world repeat tiles, which only need requesting once
+                if current.insert(tile_id) && !self.requested.contains(&tile_id) {
+                    requests.push(
+                        self.maptiler
+                            .create_request(tile.request)
+                            .with_priority(priority),
+                    );
+                }
+            }
+        }
+
+        self.requested = current;
+
+        Ok(requests)
+    }
+}
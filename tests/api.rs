@@ -1,6 +1,8 @@
-use maptiler_cloud::errors::ArgumentError;
-use maptiler_cloud::{Maptiler, TileRequest, TileSet};
-use std::env;
+use maptiler_cloud::errors::{ArgumentError, Error};
+use maptiler_cloud::{
+    Execution, Maptiler, Quota, StyleId, TileId, TileJson, TileRequest, TileSet, TileSource,
+};
+use std::{env, time::Duration};
 
 #[tokio::test]
 async fn get_tile() {
@@ -23,6 +25,21 @@ async fn get_tile() {
     assert_eq!(&tile[0..3], &[0xFF, 0xD8, 0xFF]);
 }
 
+#[tokio::test]
+async fn head_tile() {
+    let api_key = env::var("MAPTILER_KEY").expect("Environment variable MAPTILER_KEY not set");
+
+    let maptiler = Maptiler::new(api_key).unwrap();
+
+    let tile_request = TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap();
+
+    // Only the headers are requested, the tile itself is not downloaded
+    let head = maptiler.create_request(tile_request).head().await.unwrap();
+
+    assert!(head.exists());
+    assert!(head.content_length.unwrap() > 0);
+}
+
 #[test]
 fn zoom_low() {
     // TileSet::Outdoor's minimum zoom level is 5
@@ -64,3 +81,251 @@ fn y_high() {
 
     assert_eq!(request_err, ArgumentError::YTooLarge(10, 3, 8));
 }
+
+#[test]
+fn tilejson_zoom_range() {
+    // A tileset whose live zoom range is wider than the hardcoded table
+    let tilejson =
+        TileJson::from_slice(br#"{"tilejson": "2.0.0", "minzoom": 0, "maxzoom": 22}"#).unwrap();
+
+    let request = TileRequest::new_with_tilejson(TileSet::Satellite, 0, 0, 21, &tilejson)
+        .expect("Valid request failed");
+    assert_eq!(request.zoom(), 21);
+
+    let request_err = TileRequest::new_with_tilejson(TileSet::Satellite, 0, 0, 23, &tilejson)
+        .expect_err("Invalid request succeeded");
+    assert_eq!(
+        request_err,
+        ArgumentError::ZoomTooLarge(23, TileSet::Satellite, 22)
+    );
+
+    // Zoom levels past what tile coordinates can hold are rejected, whatever the TileJSON says
+    let tilejson = TileJson::from_slice(br#"{"maxzoom": 40}"#).unwrap();
+
+    let request_err = TileRequest::new_with_tilejson(TileSet::Satellite, 0, 0, 33, &tilejson)
+        .expect_err("Invalid request succeeded");
+    assert_eq!(
+        request_err,
+        ArgumentError::ZoomTooLarge(33, TileSet::Satellite, TileId::MAX_ZOOM)
+    );
+}
+
+#[test]
+fn tilejson_bounds() {
+    // Only covers the north-west quarter of the world
+    let tilejson = TileJson::from_slice(br#"{"bounds": [-180, 0, 0, 85.0511]}"#).unwrap();
+
+    TileRequest::new_with_tilejson(TileSet::Satellite, 0, 0, 1, &tilejson)
+        .expect("Valid request failed");
+
+    let request_err = TileRequest::new_with_tilejson(TileSet::Satellite, 1, 1, 1, &tilejson)
+        .expect_err("Invalid request succeeded");
+    assert_eq!(
+        request_err,
+        ArgumentError::OutsideBounds(1, 1, 1, TileSet::Satellite)
+    );
+
+    // Covers the Pacific on both sides of the antimeridian, ex: Fiji
+    let tilejson = TileJson::from_slice(br#"{"bounds": [170, -30, -170, -10]}"#).unwrap();
+
+    // The westernmost and easternmost columns at zoom 3, just south of the equator
+    TileRequest::new_with_tilejson(TileSet::Satellite, 7, 4, 3, &tilejson)
+        .expect("Tile east of the antimeridian was rejected");
+    TileRequest::new_with_tilejson(TileSet::Satellite, 0, 4, 3, &tilejson)
+        .expect("Tile west of the antimeridian was rejected");
+
+    let request_err = TileRequest::new_with_tilejson(TileSet::Satellite, 4, 4, 3, &tilejson)
+        .expect_err("Tile outside of the bounds was accepted");
+    assert_eq!(
+        request_err,
+        ArgumentError::OutsideBounds(4, 4, 3, TileSet::Satellite)
+    );
+}
+
+#[tokio::test]
+async fn tile_source_invalid_zoom() {
+    let maptiler = Maptiler::new("placeholder api key").unwrap();
+
+    // The request is validated before anything is sent
+    let source = maptiler.tile_source(TileSet::Satellite);
+    let err = source
+        .get_tile(21, 0, 0)
+        .await
+        .expect_err("Invalid request succeeded");
+
+    assert!(matches!(
+        err,
+        Error::Argument(ArgumentError::ZoomTooLarge(21, TileSet::Satellite, 20))
+    ));
+}
+
+#[test]
+fn url_templates() {
+    let maptiler = Maptiler::new("KEY").unwrap();
+
+    assert_eq!(
+        maptiler.url_template(TileSet::Satellite),
+        "https://api.maptiler.com/tiles/satellite/{z}/{x}/{y}.jpg?key=KEY"
+    );
+    assert_eq!(
+        maptiler.url_template(TileSet::Terrain3D),
+        "https://api.maptiler.com/tiles/terrain-quantized-mesh/{z}/{x}/{y}.quantized-mesh-1.0?key=KEY"
+    );
+    assert_eq!(
+        maptiler.style_url(&StyleId::new("streets-v2").unwrap()),
+        "https://api.maptiler.com/maps/streets-v2/style.json?key=KEY"
+    );
+}
+
+#[tokio::test]
+async fn quota_exceeded() {
+    let maptiler = Maptiler::builder("placeholder api key")
+        .quota(Quota::new(0, Duration::from_secs(60)))
+        .build()
+        .unwrap();
+
+    let tile_request = TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap();
+
+    // The budget is checked before anything is sent
+    let err = maptiler
+        .create_request(tile_request)
+        .execute()
+        .await
+        .expect_err("Request over quota succeeded");
+    assert!(matches!(err, Error::QuotaExceeded(0)));
+
+    let usage = maptiler.quota_usage().unwrap();
+    assert_eq!(usage.used, 0);
+    assert_eq!(usage.remaining(), 0);
+}
+
+#[tokio::test]
+async fn base_urls() {
+    // Nothing listens on port 1, so the first base URL always fails to connect
+    let maptiler = Maptiler::builder("KEY")
+        .base_urls(vec!["http://127.0.0.1:1/", "https://mirror.example.com"])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        maptiler.url_template(TileSet::Satellite),
+        "http://127.0.0.1:1/tiles/satellite/{z}/{x}/{y}.jpg?key=KEY"
+    );
+
+    let only_unreachable = Maptiler::builder("KEY")
+        .base_urls(vec!["http://127.0.0.1:1"])
+        .build()
+        .unwrap();
+
+    let tile_request = TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap();
+    let err = only_unreachable
+        .create_request(tile_request)
+        .execute()
+        .await
+        .expect_err("Request to an unreachable server succeeded");
+
+    assert!(matches!(err, Error::Backend(e) if e.is_connect()));
+}
+
+#[tokio::test]
+async fn dry_run() {
+    let maptiler = Maptiler::builder("KEY")
+        .quota(Quota::new(1, Duration::from_secs(60)))
+        .dry_run(true)
+        .build()
+        .unwrap();
+
+    let tile_request = TileRequest::new(TileSet::Outdoor, 3, 4, 5).unwrap();
+    let constructed = maptiler.create_request(tile_request);
+
+    let dry_run = match constructed.execute_or_dry_run().await.unwrap() {
+        Execution::DryRun(dry_run) => dry_run,
+        Execution::Sent(_) => panic!("Dry run sent a request"),
+    };
+
+    assert_eq!(dry_run, constructed.dry_run());
+    assert_eq!(dry_run.method, "GET");
+    assert_eq!(
+        dry_run.url,
+        "https://api.maptiler.com/tiles/outdoor/5/3/4.pbf?key=KEY"
+    );
+
+    match constructed.head_or_dry_run().await.unwrap() {
+        Execution::DryRun(dry_run) => assert_eq!(dry_run.method, "HEAD"),
+        Execution::Sent(_) => panic!("Dry run sent a request"),
+    }
+
+    // Methods that can only return a response fail instead
+    assert!(matches!(
+        constructed.execute().await,
+        Err(Error::DryRunSession)
+    ));
+    assert!(matches!(
+        constructed.head().await,
+        Err(Error::DryRunSession)
+    ));
+
+    // Nothing was sent, so nothing counts against the quota
+    assert_eq!(maptiler.quota_usage().unwrap().used, 0);
+}
+
+#[test]
+fn style_ids() {
+    StyleId::new("streets-v2").expect("Built-in style id rejected");
+    StyleId::new("0d8a8b5c-8bc4-4d97-a2a4-6a0cb4e4bca5").expect("Custom style id rejected");
+
+    for style_id in [
+        "",
+        "../style",
+        "streets?key=other",
+        "my style",
+        "streets#v2",
+    ] {
+        assert_eq!(
+            StyleId::new(style_id),
+            Err(ArgumentError::InvalidStyleId(style_id.to_string()))
+        );
+    }
+}
+
+#[test]
+fn custom_tilesets() {
+    let custom = |endpoint, extension| TileSet::Custom {
+        endpoint,
+        extension,
+    };
+
+    TileRequest::new(custom("ocean", "pbf"), 0, 0, 0).expect("Custom tileset rejected");
+
+    for (endpoint, extension) in [
+        ("", "pbf"),
+        ("..", "pbf"),
+        ("../maps", "pbf"),
+        ("ocean?key=other", "pbf"),
+        ("ocean", "pbf?key=other"),
+        ("ocean#", "pbf"),
+        ("ocean%2F", "pbf"),
+        ("my ocean", "pbf"),
+    ] {
+        assert_eq!(
+            TileRequest::new(custom(endpoint, extension), 0, 0, 0),
+            Err(ArgumentError::InvalidCustomTileSet(
+                endpoint.to_string(),
+                extension.to_string()
+            ))
+        );
+    }
+
+    // Characters that are allowed are still percent-encoded where URLs need it
+    let maptiler = Maptiler::new("KEY").unwrap();
+    let tile_request = TileRequest::new(custom("océan+1", "pbf"), 0, 0, 0).unwrap();
+
+    assert_eq!(
+        maptiler.create_request(tile_request).dry_run().url,
+        "https://api.maptiler.com/tiles/oc%C3%A9an%2B1/0/0/0.pbf?key=KEY"
+    );
+    assert_eq!(
+        maptiler.url_template(custom("ocean&v=2", "pbf")),
+        "https://api.maptiler.com/tiles/ocean%26v%3D2/{z}/{x}/{y}.pbf?key=KEY"
+    );
+}
@@ -0,0 +1,742 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http::{HeaderMap, StatusCode};
+use maptiler_cloud::{
+    backend::{
+        BackendError, BackendErrorKind, BoxFuture, HttpBackend, HttpRequest, HttpResponse,
+        ResponseBody,
+    },
+    errors::Error,
+    AdaptiveConcurrency, CircuitBreaker, DownloadQueue, Maptiler, Priority, Quota, Tile,
+    TileRequest, TileSet,
+};
+
+const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x00, 0xFF, 0xD9];
+
+// A backend that answers every request with the next canned body, and records the URLs requested
+#[derive(Default)]
+struct CannedBackend {
+    bodies: Mutex<Vec<Result<&'static [u8], BackendErrorKind>>>,
+    urls: Mutex<Vec<String>>,
+    delay: Duration,
+    // The time to answer each request, if it differs between requests
+    delays: Mutex<Vec<Duration>>,
+    headers: HeaderMap,
+}
+
+impl CannedBackend {
+    fn new(bodies: Vec<Result<&'static [u8], BackendErrorKind>>) -> Arc<Self> {
+        Self::slow(bodies, Duration::ZERO)
+    }
+
+    // Takes the given time to answer each request
+    fn slow(bodies: Vec<Result<&'static [u8], BackendErrorKind>>, delay: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            bodies: Mutex::new(bodies.into_iter().rev().collect()),
+            urls: Mutex::new(Vec::new()),
+            delay,
+            delays: Mutex::new(Vec::new()),
+            headers: HeaderMap::new(),
+        })
+    }
+
+    // Takes its own time to answer each request
+    fn with_delays(
+        responses: Vec<(Duration, Result<&'static [u8], BackendErrorKind>)>,
+    ) -> Arc<Self> {
+        let (delays, bodies): (Vec<_>, Vec<_>) = responses.into_iter().rev().unzip();
+
+        Arc::new(Self {
+            bodies: Mutex::new(bodies),
+            urls: Mutex::new(Vec::new()),
+            delay: Duration::ZERO,
+            delays: Mutex::new(delays),
+            headers: HeaderMap::new(),
+        })
+    }
+
+    // Sends the given headers with every response
+    fn with_headers(
+        bodies: Vec<Result<&'static [u8], BackendErrorKind>>,
+        headers: HeaderMap,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            bodies: Mutex::new(bodies.into_iter().rev().collect()),
+            urls: Mutex::new(Vec::new()),
+            delay: Duration::ZERO,
+            delays: Mutex::new(Vec::new()),
+            headers,
+        })
+    }
+
+    fn urls(&self) -> Vec<String> {
+        self.urls.lock().unwrap().clone()
+    }
+}
+
+struct OneChunk(Option<Bytes>);
+
+impl ResponseBody for OneChunk {
+    fn chunk(&mut self) -> BoxFuture<'_, Result<Option<Bytes>, BackendError>> {
+        let chunk = self.0.take();
+        Box::pin(async move { Ok(chunk) })
+    }
+}
+
+impl HttpBackend for CannedBackend {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BackendError>> {
+        self.urls.lock().unwrap().push(request.url);
+        let body = self
+            .bodies
+            .lock()
+            .unwrap()
+            .pop()
+            .expect("Too many requests");
+        let delay = self.delays.lock().unwrap().pop().unwrap_or(self.delay);
+        let headers = self.headers.clone();
+
+        Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            let body = body.map_err(|kind| BackendError::new(kind, "canned failure"))?;
+
+            Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers,
+                body: Box::new(OneChunk(Some(Bytes::from_static(body)))),
+            })
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+fn satellite_tile() -> TileRequest {
+    TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap()
+}
+
+#[tokio::test]
+async fn custom_backend() {
+    let backend = CannedBackend::new(vec![Ok(JPEG)]);
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .build()
+        .unwrap();
+
+    let tile = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .unwrap();
+
+    assert_eq!(tile, JPEG);
+    assert_eq!(
+        backend.urls(),
+        vec!["https://api.maptiler.com/tiles/satellite/0/0/0.jpg?key=KEY"]
+    );
+}
+
+#[tokio::test]
+async fn failover() {
+    let backend = CannedBackend::new(vec![Err(BackendErrorKind::Connect), Ok(JPEG)]);
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .base_urls(vec![
+            "https://primary.example.com",
+            "https://mirror.example.com",
+        ])
+        .build()
+        .unwrap();
+
+    maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        backend.urls(),
+        vec![
+            "https://primary.example.com/tiles/satellite/0/0/0.jpg?key=KEY",
+            "https://mirror.example.com/tiles/satellite/0/0/0.jpg?key=KEY",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn corrupt_tile_retries() {
+    // A PNG is not a valid satellite tile
+    let png: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+    let backend = CannedBackend::new(vec![Ok(png), Ok(&JPEG[..4]), Ok(JPEG)]);
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .corrupt_tile_retries(2)
+        .build()
+        .unwrap();
+
+    let tile = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(tile, JPEG);
+
+    let backend = CannedBackend::new(vec![Ok(png)]);
+    let maptiler = Maptiler::builder("KEY").backend(backend).build().unwrap();
+
+    let err = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .expect_err("Corrupt tile was accepted");
+    assert!(matches!(err, Error::CorruptTile("jpg")));
+}
+
+// The clock is paused, so that it only moves on once every task is waiting, ex: for the slot
+#[tokio::test(start_paused = true)]
+async fn request_priority() {
+    let backend = CannedBackend::slow(vec![Ok(JPEG); 4], Duration::from_millis(50));
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .max_concurrent_requests(1)
+        .build()
+        .unwrap();
+
+    let spawn = |x, priority| {
+        let request = maptiler
+            .create_request(TileRequest::new(TileSet::Satellite, x, 0, 2).unwrap())
+            .with_priority(priority);
+
+        tokio::spawn(async move { request.execute().await.unwrap() })
+    };
+
+    // The first request takes the only slot, and the rest queue up behind it in this order. Each
+    // sleep lets the task spawned before it run until it waits for the slot.
+    let mut tasks = vec![spawn(0, Priority::Normal)];
+    for (x, priority) in [
+        (1, Priority::Low),
+        (2, Priority::Normal),
+        (3, Priority::High),
+    ]
+    .iter()
+    .copied()
+    {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        tasks.push(spawn(x, priority));
+    }
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let paths: Vec<String> = backend
+        .urls()
+        .iter()
+        .map(|url| {
+            url.trim_start_matches("https://api.maptiler.com/tiles/satellite/")
+                .to_string()
+        })
+        .collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            "2/0/0.jpg?key=KEY",
+            "2/3/0.jpg?key=KEY",
+            "2/2/0.jpg?key=KEY",
+            "2/1/0.jpg?key=KEY"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn download_queue() {
+    let backend = CannedBackend::new(vec![Ok(JPEG); 3]);
+    let maptiler = Maptiler::builder("KEY").backend(backend).build().unwrap();
+
+    let queue = DownloadQueue::new(2);
+    let submit = || queue.submit(maptiler.create_request(satellite_tile()));
+
+    let first = submit().await;
+    let second = submit().await;
+
+    // The queue is full, so the third submission has to wait
+    let third = submit();
+    tokio::pin!(third);
+    assert!(tokio::time::timeout(Duration::from_millis(20), &mut third)
+        .await
+        .is_err());
+
+    assert_eq!(first.execute().await.unwrap(), JPEG);
+    let third = third.await;
+
+    assert_eq!(second.execute().await.unwrap(), JPEG);
+    assert_eq!(third.execute().await.unwrap(), JPEG);
+}
+
+// A backend whose response bodies never arrive
+struct StalledBackend;
+
+struct StalledBody;
+
+impl ResponseBody for StalledBody {
+    fn chunk(&mut self) -> BoxFuture<'_, Result<Option<Bytes>, BackendError>> {
+        Box::pin(std::future::pending())
+    }
+}
+
+impl HttpBackend for StalledBackend {
+    fn send(&self, _request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BackendError>> {
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: Box::new(StalledBody),
+            })
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+#[tokio::test]
+async fn read_timeout() {
+    let maptiler = Maptiler::builder("KEY")
+        .backend(Arc::new(StalledBackend))
+        .read_timeout(Duration::from_millis(20))
+        .build()
+        .unwrap();
+
+    let err = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .expect_err("Stalled body was not timed out");
+
+    assert!(matches!(err, Error::Backend(e) if e.is_timeout()));
+}
+
+#[tokio::test]
+async fn circuit_breaker() {
+    let backend = CannedBackend::new(vec![
+        Err(BackendErrorKind::Connect),
+        Err(BackendErrorKind::Connect),
+        Ok(JPEG),
+    ]);
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .circuit_breaker(CircuitBreaker::new(1.0, 2, Duration::from_millis(50)))
+        .build()
+        .unwrap();
+
+    let execute = || async { maptiler.create_request(satellite_tile()).execute().await };
+
+    assert!(matches!(execute().await, Err(Error::Backend(_))));
+    assert!(matches!(execute().await, Err(Error::Backend(_))));
+
+    // The breaker is open, so this fails without being sent
+    assert!(matches!(execute().await, Err(Error::CircuitOpen)));
+    assert_eq!(backend.urls().len(), 2);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(execute().await.unwrap(), JPEG);
+    assert_eq!(backend.urls().len(), 3);
+}
+
+#[tokio::test]
+async fn circuit_breaker_needs_failures() {
+    let backend = CannedBackend::new(vec![Ok(JPEG), Ok(JPEG), Ok(JPEG)]);
+
+    // A failure rate of 0.0 still needs a failure to open the breaker
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .circuit_breaker(CircuitBreaker::new(0.0, 2, Duration::from_secs(60)))
+        .build()
+        .unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(
+            maptiler
+                .create_request(satellite_tile())
+                .execute()
+                .await
+                .unwrap(),
+            JPEG
+        );
+    }
+
+    assert_eq!(
+        CircuitBreaker::new(-1.0, 2, Duration::ZERO).failure_rate(),
+        0.0
+    );
+    assert_eq!(
+        CircuitBreaker::new(2.0, 2, Duration::ZERO).failure_rate(),
+        1.0
+    );
+    assert_eq!(
+        CircuitBreaker::new(f64::NAN, 2, Duration::ZERO).failure_rate(),
+        1.0
+    );
+}
+
+#[tokio::test]
+async fn circuit_breaker_closes_on_probe() {
+    let cooldown = Duration::from_millis(500);
+    let backend = CannedBackend::with_delays(vec![
+        // Still in flight when the breaker opens, and finishes while the probe is
+        (Duration::from_millis(700), Ok(JPEG)),
+        (Duration::ZERO, Err(BackendErrorKind::Connect)),
+        // The probe, which doesn't finish during the test
+        (Duration::from_secs(60), Ok(JPEG)),
+    ]);
+
+    let maptiler = Arc::new(
+        Maptiler::builder("KEY")
+            .backend(backend.clone())
+            .circuit_breaker(CircuitBreaker::new(1.0, 1, cooldown))
+            .build()
+            .unwrap(),
+    );
+
+    let execute = |maptiler: Arc<Maptiler>| async move {
+        maptiler.create_request(satellite_tile()).execute().await
+    };
+
+    let start = Instant::now();
+    let in_flight = tokio::spawn(execute(maptiler.clone()));
+
+    while backend.urls().is_empty() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    assert!(matches!(
+        execute(maptiler.clone()).await,
+        Err(Error::Backend(_))
+    ));
+    assert!(matches!(
+        execute(maptiler.clone()).await,
+        Err(Error::CircuitOpen)
+    ));
+
+    tokio::time::sleep(cooldown + Duration::from_millis(50)).await;
+    tokio::spawn(execute(maptiler.clone()));
+
+    while backend.urls().len() < 3 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    assert_eq!(in_flight.await.unwrap().unwrap(), JPEG);
+
+    // The request that finished wasn't the probe, so the breaker is still waiting on it
+    assert!(start.elapsed() < cooldown * 2);
+    assert!(matches!(
+        execute(maptiler.clone()).await,
+        Err(Error::CircuitOpen)
+    ));
+    assert_eq!(backend.urls().len(), 3);
+}
+
+#[tokio::test]
+async fn quota_wait_for_window() {
+    let window = Duration::from_millis(100);
+    let backend = CannedBackend::new(vec![Ok(JPEG), Ok(JPEG)]);
+
+    // The first window starts when the session is built
+    let start = Instant::now();
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .quota(Quota::new(1, window).wait_for_window())
+        .build()
+        .unwrap();
+
+    let execute = || async { maptiler.create_request(satellite_tile()).execute().await };
+
+    assert_eq!(execute().await.unwrap(), JPEG);
+
+    // The budget is used up, so this waits for the next window instead of failing
+    assert_eq!(execute().await.unwrap(), JPEG);
+    assert!(start.elapsed() >= window);
+    assert_eq!(backend.urls().len(), 2);
+    assert_eq!(maptiler.quota_usage().unwrap().used, 1);
+}
+
+#[tokio::test]
+async fn quota_counts_head_requests() {
+    let backend = CannedBackend::new(vec![Ok(JPEG)]);
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .quota(Quota::new(1, Duration::from_secs(60)))
+        .build()
+        .unwrap();
+    let constructed = maptiler.create_request(satellite_tile());
+
+    assert_eq!(constructed.head().await.unwrap().status, StatusCode::OK);
+    assert_eq!(maptiler.quota_usage().unwrap().used, 1);
+
+    let err = constructed
+        .head()
+        .await
+        .expect_err("HEAD request over quota was sent");
+    assert!(matches!(err, Error::QuotaExceeded(1)));
+    assert_eq!(backend.urls().len(), 1);
+}
+
+#[tokio::test]
+async fn max_response_size() {
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::new(vec![Ok(JPEG)]))
+        .max_response_size(JPEG.len() as u64 - 1)
+        .build()
+        .unwrap();
+
+    let err = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .expect_err("Oversized response was accepted");
+    assert!(matches!(err, Error::ResponseTooLarge(7)));
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::new(vec![Ok(JPEG)]))
+        .max_response_size(JPEG.len() as u64)
+        .build()
+        .unwrap();
+
+    let tile = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(tile, JPEG);
+}
+
+#[tokio::test]
+async fn oversized_content_length() {
+    let mut headers = HeaderMap::new();
+    headers.insert(http::header::CONTENT_LENGTH, u64::MAX.into());
+
+    // The header is used to check the body, but not to size its buffer, with or without a pool
+    for max_buffers in [None, Some(1)] {
+        let mut builder = Maptiler::builder("KEY")
+            .backend(CannedBackend::with_headers(vec![Ok(JPEG)], headers.clone()));
+
+        if let Some(max_buffers) = max_buffers {
+            builder = builder.buffer_pool(max_buffers);
+        }
+
+        let err = builder
+            .build()
+            .unwrap()
+            .create_request(satellite_tile())
+            .execute()
+            .await
+            .expect_err("Truncated response was accepted");
+        assert!(matches!(err, Error::TruncatedTile(u64::MAX, 8)));
+    }
+}
+
+#[tokio::test]
+async fn decompressed_body() {
+    // The backend decompressed the body, so it is longer than the Content-Length of the response
+    let mut headers = HeaderMap::new();
+    headers.insert(http::header::CONTENT_LENGTH, 4.into());
+    headers.insert(
+        http::header::CONTENT_ENCODING,
+        http::HeaderValue::from_static("gzip"),
+    );
+
+    let tile = Maptiler::builder("KEY")
+        .backend(CannedBackend::with_headers(vec![Ok(JPEG)], headers))
+        .build()
+        .unwrap()
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(tile, JPEG);
+}
+
+#[tokio::test]
+async fn buffer_pool() {
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::new(vec![Ok(JPEG), Ok(JPEG)]))
+        .buffer_pool(1)
+        .build()
+        .unwrap();
+
+    let mut recycled = Vec::with_capacity(4096);
+    recycled.extend_from_slice(b"stale");
+    let allocation = recycled.as_ptr();
+    maptiler.recycle(recycled);
+
+    let tile = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(tile, JPEG);
+    assert_eq!(tile.as_ptr(), allocation);
+
+    // The pool is empty again, so the next tile gets a new buffer
+    let tile = maptiler
+        .create_request(satellite_tile())
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(tile, JPEG);
+    assert_ne!(tile.as_ptr(), allocation);
+}
+
+#[tokio::test]
+async fn adaptive_concurrency() {
+    let mut bodies = vec![Ok(JPEG); 6];
+    bodies.extend([
+        Err(BackendErrorKind::Timeout),
+        Err(BackendErrorKind::Timeout),
+    ]);
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::new(bodies))
+        .adaptive_concurrency(AdaptiveConcurrency::new(1, 4, Duration::from_secs(10)))
+        .build()
+        .unwrap();
+    assert_eq!(maptiler.concurrency_limit(), Some(1));
+
+    // The limit grows by one for every full limit of fast requests: 1, then 2, then 3
+    for expected in [2, 2, 3, 3, 3, 4] {
+        maptiler
+            .create_request(satellite_tile())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(maptiler.concurrency_limit(), Some(expected));
+    }
+
+    // Only the first of a burst of timeouts halves it
+    for _ in 0..2 {
+        maptiler
+            .create_request(satellite_tile())
+            .execute()
+            .await
+            .expect_err("Timed out request succeeded");
+        assert_eq!(maptiler.concurrency_limit(), Some(2));
+    }
+
+    // Slow responses count as overload too, and the limit never drops below the minimum
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::slow(
+            vec![Ok(JPEG), Ok(JPEG)],
+            Duration::from_millis(20),
+        ))
+        .adaptive_concurrency(AdaptiveConcurrency::new(1, 4, Duration::from_millis(1)))
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        maptiler
+            .create_request(satellite_tile())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(maptiler.concurrency_limit(), Some(1));
+    }
+}
+
+#[tokio::test]
+async fn fetch_tile() {
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::new(vec![Ok(JPEG)]))
+        .build()
+        .unwrap();
+
+    match maptiler.fetch_tile(satellite_tile()).await.unwrap() {
+        Tile::Satellite(jpg) => assert_eq!(jpg.as_bytes(), JPEG),
+        tile => panic!("Satellite tile was returned as {:?}", tile),
+    }
+
+    let pbf = Tile::new(TileSet::Outdoor, vec![0x1A]);
+    assert!(matches!(pbf, Tile::Vector(_)));
+    assert_eq!(pbf.into_bytes(), [0x1A]);
+
+    let custom = TileSet::Custom {
+        endpoint: "ocean",
+        extension: "webp",
+    };
+    assert!(matches!(
+        Tile::new(custom, Vec::new()),
+        Tile::Custom {
+            extension: "webp",
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn execute_to_file() {
+    let directory = std::env::temp_dir().join(format!("maptiler-cloud-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("satellite.jpg");
+
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::new(vec![
+            Ok(JPEG),
+            Err(BackendErrorKind::Connect),
+        ]))
+        .build()
+        .unwrap();
+
+    let constructed = maptiler.create_request(satellite_tile());
+    constructed.execute_to_file(&path).await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), JPEG);
+
+    // A failed request leaves the saved tile alone, and no temporary file behind
+    constructed
+        .execute_to_file(&path)
+        .await
+        .expect_err("Failed request was saved");
+    assert_eq!(std::fs::read(&path).unwrap(), JPEG);
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn read_response() {
+    let maptiler = Maptiler::builder("KEY")
+        .backend(CannedBackend::new(Vec::new()))
+        .build()
+        .unwrap();
+    let constructed = maptiler.create_request(satellite_tile());
+
+    let headers = HeaderMap::new();
+    let png = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+    assert_eq!(
+        constructed
+            .read_response(StatusCode::OK, &headers, JPEG.to_vec())
+            .unwrap(),
+        JPEG
+    );
+    assert!(matches!(
+        constructed.read_response(StatusCode::NOT_FOUND, &headers, Vec::new()),
+        Err(Error::Http(StatusCode::NOT_FOUND))
+    ));
+    assert!(matches!(
+        constructed.read_response(StatusCode::OK, &headers, png),
+        Err(Error::CorruptTile("jpg"))
+    ));
+}
@@ -0,0 +1,95 @@
+use maptiler_cloud::{errors::ConfigError, Config, Maptiler, TileSet};
+
+#[test]
+fn json_config() {
+    let config = Config::from_json_str(
+        r#"{
+            "api_key": "KEY",
+            "base_urls": ["https://mirror.example.com/"],
+            "quota": { "limit": 100, "window_secs": 60 },
+            "default_tileset": "outdoor"
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.quota.unwrap().limit, 100);
+    assert_eq!(config.default_tileset().unwrap(), Some(TileSet::Outdoor));
+
+    let maptiler = Maptiler::from_config(&config).unwrap();
+
+    assert_eq!(maptiler.quota_usage().unwrap().limit, 100);
+    assert_eq!(
+        maptiler.url_template(TileSet::Outdoor),
+        "https://mirror.example.com/tiles/outdoor/{z}/{x}/{y}.pbf?key=KEY"
+    );
+}
+
+#[test]
+fn session_settings() {
+    let config = Config::from_json_str(
+        r#"{
+            "api_key": "SECRET KEY",
+            "max_response_size": 1000,
+            "max_concurrent_requests": 4,
+            "adaptive_concurrency": { "min": 2, "max": 16, "target_latency_ms": 500 },
+            "circuit_breaker": { "failure_rate": 0.5, "window": 10, "cooldown_secs": 30 },
+            "connect_timeout_ms": 5000,
+            "read_timeout_ms": 10000
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.circuit_breaker.unwrap().window, 10);
+    assert_eq!(config.read_timeout_ms, Some(10000));
+
+    // The adaptive limit starts at its minimum
+    let maptiler = Maptiler::from_config(&config).unwrap();
+    assert_eq!(maptiler.concurrency_limit(), Some(2));
+
+    // The key is kept out of logs
+    let debug = format!("{:?}", config);
+    assert!(!debug.contains("SECRET KEY"));
+    assert!(debug.contains("max_concurrent_requests: Some(4)"));
+}
+
+#[test]
+fn api_key_from_env() {
+    std::env::set_var("MAPTILER_CLOUD_CONFIG_TEST_KEY", "ENV KEY");
+
+    let config =
+        Config::from_json_str(r#"{ "api_key_env": "MAPTILER_CLOUD_CONFIG_TEST_KEY" }"#).unwrap();
+    assert_eq!(config.api_key().unwrap(), "ENV KEY");
+
+    let config = Config::from_json_str(r#"{ "api_key_env": "MAPTILER_CLOUD_UNSET" }"#).unwrap();
+    assert!(matches!(
+        config.api_key(),
+        Err(ConfigError::MissingApiKey(name)) if name == "MAPTILER_CLOUD_UNSET"
+    ));
+}
+
+#[test]
+fn unknown_fields() {
+    let err = Config::from_json_str(r#"{ "api_kye": "KEY" }"#).expect_err("Invalid config loaded");
+
+    assert!(matches!(err, ConfigError::Json(_)));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_config() {
+    let config = Config::from_toml_str(
+        r#"
+        api_key = "KEY"
+        corrupt_tile_retries = 2
+
+        [quota]
+        limit = 10
+        window_secs = 1
+        wait = true
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.corrupt_tile_retries, 2);
+    assert!(config.quota.unwrap().wait);
+}
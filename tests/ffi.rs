@@ -0,0 +1,47 @@
+#![cfg(feature = "ffi")]
+
+use std::{os::raw::c_char, ptr};
+
+use maptiler_cloud::ffi::{
+    maptiler_fetch_tile, maptiler_session_free, maptiler_session_new, MAPTILER_INVALID_ARGUMENT,
+};
+
+fn c_str(bytes: &'static [u8]) -> *const c_char {
+    bytes.as_ptr().cast()
+}
+
+#[test]
+fn invalid_arguments() {
+    unsafe {
+        assert!(maptiler_session_new(ptr::null()).is_null());
+
+        let session = maptiler_session_new(c_str(b"KEY\0"));
+        assert!(!session.is_null());
+
+        let mut buffer = [0u8; 16];
+        let mut written = 0;
+
+        let mut fetch = |endpoint: *const _, zoom| {
+            maptiler_fetch_tile(
+                session,
+                endpoint,
+                zoom,
+                0,
+                0,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut written,
+            )
+        };
+
+        // None of these reach the network
+        assert_eq!(fetch(ptr::null(), 0), MAPTILER_INVALID_ARGUMENT);
+        assert_eq!(
+            fetch(c_str(b"not-a-tileset\0"), 0),
+            MAPTILER_INVALID_ARGUMENT
+        );
+        assert_eq!(fetch(c_str(b"satellite\0"), 99), MAPTILER_INVALID_ARGUMENT);
+
+        maptiler_session_free(session);
+    }
+}
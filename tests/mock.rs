@@ -0,0 +1,221 @@
+#![cfg(feature = "test-util")]
+
+use std::time::{Duration, Instant};
+
+use maptiler_cloud::{
+    errors::Error,
+    fixtures,
+    mock::{MockResponse, MockServer},
+    Maptiler, RequestType, TileJsonRequest, TileRequest, TileSet,
+};
+
+const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x00, 0xFF, 0xD9];
+
+fn session(server: &MockServer) -> Maptiler {
+    Maptiler::builder("KEY")
+        .base_urls(vec![server.base_url()])
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn records_requests() {
+    let server = MockServer::start().await.unwrap();
+    let tile_request = TileRequest::new(TileSet::Satellite, 1, 2, 3).unwrap();
+    server.mock_request(tile_request, JPEG);
+
+    let tile = session(&server)
+        .create_request(tile_request)
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(tile, JPEG);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, http::Method::GET);
+    assert_eq!(requests[0].path, "/tiles/satellite/3/1/2.jpg");
+    assert_eq!(requests[0].query.as_deref(), Some("key=KEY"));
+}
+
+#[tokio::test]
+async fn unmocked_path() {
+    let server = MockServer::start().await.unwrap();
+
+    let err = session(&server)
+        .create_request(TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap())
+        .execute()
+        .await
+        .expect_err("Unmocked request succeeded");
+
+    assert!(matches!(err, Error::Http(http::StatusCode::NOT_FOUND)));
+}
+
+#[tokio::test]
+async fn head() {
+    let server = MockServer::start().await.unwrap();
+    let tile_request = TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap();
+    server.mock(
+        "/tiles/satellite/0/0/0.jpg",
+        MockResponse::ok(JPEG).header(http::header::ETAG, "\"abc\""),
+    );
+
+    let head = session(&server)
+        .create_request(tile_request)
+        .head()
+        .await
+        .unwrap();
+
+    assert!(head.exists());
+    assert_eq!(head.content_length, Some(JPEG.len() as u64));
+    assert_eq!(head.etag.as_deref(), Some("\"abc\""));
+}
+
+#[tokio::test]
+async fn tilejson() {
+    let server = MockServer::start().await.unwrap();
+    server.mock_request(
+        TileJsonRequest::new(TileSet::Outdoor),
+        r#"{"minzoom": 5, "maxzoom": 16}"#,
+    );
+
+    let tilejson = session(&server)
+        .fetch_tilejson(TileSet::Outdoor)
+        .await
+        .unwrap();
+
+    assert_eq!(tilejson.maxzoom, 16);
+}
+
+#[tokio::test]
+async fn bandwidth_limit() {
+    let server = MockServer::start().await.unwrap();
+    let tile_request = TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap();
+    server.mock_request(tile_request, JPEG);
+
+    // Each tile is 8 bytes, and one second worth of bytes may be downloaded right away
+    let maptiler = Maptiler::builder("KEY")
+        .base_urls(vec![server.base_url()])
+        .max_bandwidth(40)
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    for _ in 0..10 {
+        maptiler
+            .create_request(tile_request)
+            .execute()
+            .await
+            .unwrap();
+    }
+
+    assert!(start.elapsed() >= Duration::from_millis(900));
+}
+
+#[tokio::test]
+async fn fixtures() {
+    let server = MockServer::start().await.unwrap();
+    server.serve_fixtures();
+
+    let maptiler = session(&server);
+
+    for (set, fixture) in [
+        (TileSet::Satellite, fixtures::JPG),
+        (TileSet::TerrainRGB, fixtures::PNG),
+        (TileSet::Outdoor, fixtures::PBF),
+        (TileSet::Terrain3D, fixtures::QUANTIZED_MESH),
+    ] {
+        let tile_request = TileRequest::new(set, 0, 0, set.min_zoom()).unwrap();
+
+        // Fixtures pass the same corruption checks as real tiles
+        let tile = maptiler
+            .create_request(tile_request)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(tile, fixture);
+        assert_eq!(fixtures::for_tileset(set), Some(fixture));
+    }
+
+    // Only tile paths are answered with fixtures
+    let err = maptiler
+        .fetch_tilejson(TileSet::Satellite)
+        .await
+        .expect_err("Unmocked TileJSON request succeeded");
+    assert!(matches!(err, Error::Http(http::StatusCode::NOT_FOUND)));
+}
+
+#[tokio::test]
+async fn available_tilesets() {
+    let server = MockServer::start().await.unwrap();
+    server.mock_request(TileJsonRequest::new(TileSet::Satellite), "{}");
+    server.mock_request(TileJsonRequest::new(TileSet::Outdoor), "{}");
+    server.mock(
+        "/tiles/terrain-rgb/tiles.json",
+        MockResponse::status(http::StatusCode::FORBIDDEN),
+    );
+
+    // Everything else isn't found
+    let available = session(&server).available_tilesets().await.unwrap();
+    assert_eq!(available, [TileSet::Outdoor, TileSet::Satellite]);
+    assert_eq!(server.requests().len(), TileSet::BUILT_IN.len());
+
+    server.mock(
+        "/tiles/land/tiles.json",
+        MockResponse::status(http::StatusCode::UNAUTHORIZED),
+    );
+
+    let err = session(&server)
+        .available_tilesets()
+        .await
+        .expect_err("Invalid key was reported as having no tilesets");
+    assert!(matches!(err, Error::Http(http::StatusCode::UNAUTHORIZED)));
+
+    // A restricted key is forbidden from every tileset
+    let server = MockServer::start().await.unwrap();
+
+    for &set in TileSet::BUILT_IN.iter() {
+        server.mock(
+            format!("/tiles/{}/tiles.json", set.endpoint()),
+            MockResponse::status(http::StatusCode::FORBIDDEN),
+        );
+    }
+
+    let err = session(&server)
+        .available_tilesets()
+        .await
+        .expect_err("Forbidden key was reported as having no tilesets");
+    assert!(matches!(err, Error::Http(http::StatusCode::FORBIDDEN)));
+}
+
+#[tokio::test]
+async fn fallback_tilesets() {
+    let server = MockServer::start().await.unwrap();
+    let fallback = TileRequest::new(TileSet::SatelliteMediumRes2018, 1, 2, 3).unwrap();
+    server.mock_request(fallback, JPEG);
+
+    // Satellite has no tile there, and the 2016 tileset doesn't either
+    let (source, tile) = session(&server)
+        .create_request(TileRequest::new(TileSet::Satellite, 1, 2, 3).unwrap())
+        .with_fallbacks([
+            TileSet::SatelliteMediumRes2016,
+            TileSet::SatelliteMediumRes2018,
+        ])
+        .execute_with_source()
+        .await
+        .unwrap();
+    assert_eq!(tile, JPEG);
+    assert!(matches!(source, RequestType::TileRequest(request) if request == fallback));
+    assert_eq!(server.requests().len(), 3);
+
+    // Fallbacks without the zoom level are skipped, and the first error is returned
+    let err = session(&server)
+        .create_request(TileRequest::new(TileSet::Satellite, 0, 0, 15).unwrap())
+        .with_fallbacks([TileSet::SatelliteMediumRes2018])
+        .execute()
+        .await
+        .expect_err("Missing tile was found");
+    assert!(matches!(err, Error::Http(http::StatusCode::NOT_FOUND)));
+    assert_eq!(server.requests().len(), 4);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "poll")]
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use maptiler_cloud::{
+    poll::{PollingTiles, TileState},
+    TileSource,
+};
+
+// Returns the zoom level as the tile, and fails for tiles at x = 1
+#[derive(Default)]
+struct CountingSource {
+    requests: Arc<AtomicUsize>,
+}
+
+impl TileSource for CountingSource {
+    type Error = String;
+
+    fn get_tile(
+        &self,
+        zoom: u32,
+        x: u32,
+        _y: u32,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+
+            match x {
+                1 => Err(String::from("no such tile")),
+                _ => Ok(vec![zoom as u8]),
+            }
+        }
+    }
+}
+
+// Asks for a tile over and over like a GUI drawing frames, until it is no longer loading
+async fn wait_for(
+    tiles: &PollingTiles<CountingSource>,
+    zoom: u32,
+    x: u32,
+    y: u32,
+) -> TileState<String> {
+    let frames = async {
+        loop {
+            match tiles.get_tile(zoom, x, y) {
+                TileState::Loading => tokio::time::sleep(Duration::from_millis(1)).await,
+                state => return state,
+            }
+        }
+    };
+
+    tokio::time::timeout(Duration::from_secs(5), frames)
+        .await
+        .expect("Tile never finished loading")
+}
+
+#[tokio::test]
+async fn polling_tiles() {
+    let source = CountingSource::default();
+    let requests = Arc::clone(&source.requests);
+    let tiles = PollingTiles::new(source, tokio::runtime::Handle::current());
+
+    // Every frame asks for the tile again, but it is only downloaded once
+    assert!(matches!(tiles.get_tile(3, 0, 0), TileState::Loading));
+    assert!(matches!(tiles.get_tile(3, 0, 0), TileState::Loading));
+
+    match wait_for(&tiles, 3, 0, 0).await {
+        TileState::Loaded(bytes) => assert_eq!(&bytes[..], &[3]),
+        state => panic!("Tile was not loaded: {:?}", state),
+    }
+    assert!(matches!(tiles.get_tile(3, 0, 0), TileState::Loaded(_)));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    assert!(matches!(
+        wait_for(&tiles, 3, 1, 0).await,
+        TileState::Failed(_)
+    ));
+    assert!(matches!(tiles.get_tile(3, 1, 0), TileState::Failed(_)));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    // Failed tiles are only downloaded again once they are forgotten
+    tiles.forget(3, 1, 0);
+    assert!(matches!(tiles.get_tile(3, 1, 0), TileState::Loading));
+    assert!(matches!(
+        wait_for(&tiles, 3, 1, 0).await,
+        TileState::Failed(_)
+    ));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,78 @@
+use maptiler_cloud::{
+    projection::{self, MAX_EXTENT, MAX_LATITUDE},
+    TileX, TileY,
+};
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} is not within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+#[test]
+fn meters() {
+    assert_eq!(projection::to_meters(0.0, 0.0), (0.0, 0.0));
+
+    let (x, y) = projection::to_meters(180.0, MAX_LATITUDE);
+    assert_close(x, 20_037_508.342_789_244, 1e-6);
+    assert_close(y, 20_037_508.342_789_244, 1e-6);
+
+    // Bern, Switzerland, as given by EPSG:3857 references
+    let (x, y) = projection::to_meters(7.4474, 46.9481);
+    assert_close(x, 829_040.8, 1.0);
+    assert_close(y, 5_933_606.8, 1.0);
+
+    for (longitude, latitude) in [(-122.4194, 37.7749), (151.2093, -33.8688), (0.0, 85.0)] {
+        let (x, y) = projection::to_meters(longitude, latitude);
+        let (back_longitude, back_latitude) = projection::from_meters(x, y);
+
+        assert_close(back_longitude, longitude, 1e-9);
+        assert_close(back_latitude, latitude, 1e-9);
+    }
+
+    // The poles are clamped to the edge of the map
+    assert_close(projection::to_meters(0.0, 90.0).1, MAX_EXTENT, 1e-6);
+}
+
+#[test]
+fn pixels() {
+    assert_eq!(projection::to_pixels(0.0, 0.0, 0, 256), (128.0, 128.0));
+    assert_eq!(projection::to_pixels(-180.0, 0.0, 2, 512), (0.0, 1024.0));
+
+    let (x, y) = projection::to_pixels(7.4474, 46.9481, 14, 256);
+    let (longitude, latitude) = projection::from_pixels(x, y, 14, 256);
+    assert_close(longitude, 7.4474, 1e-9);
+    assert_close(latitude, 46.9481, 1e-9);
+}
+
+#[test]
+fn tiles() {
+    let tile_at = |longitude, latitude, zoom| {
+        let (x, y) = projection::tile_at(longitude, latitude, zoom);
+        (x.get(), y.get())
+    };
+
+    assert_eq!(tile_at(7.4474, 46.9481, 10), (533, 360));
+    assert_eq!(tile_at(180.0, -90.0, 3), (7, 7));
+    assert_eq!(tile_at(-180.0, 90.0, 3), (0, 0));
+    assert_eq!(
+        projection::tile_at(7.4474, 46.9481, 10),
+        (TileX::new(533), TileY::new(360))
+    );
+
+    let [west, south, east, north] = projection::tile_bounds(0, 0, 0);
+    assert_close(west, -180.0, 1e-9);
+    assert_close(south, -MAX_LATITUDE, 1e-9);
+    assert_close(east, 180.0, 1e-9);
+    assert_close(north, MAX_LATITUDE, 1e-9);
+
+    let [west, south, east, north] = projection::tile_bounds(1, 1, 0);
+    assert_close(west, 0.0, 1e-9);
+    assert_close(south, 0.0, 1e-9);
+    assert_close(east, 180.0, 1e-9);
+    assert_close(north, MAX_LATITUDE, 1e-9);
+}
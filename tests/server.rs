@@ -0,0 +1,56 @@
+#![cfg(feature = "server")]
+
+use hyper::{Body, Request, StatusCode};
+use maptiler_cloud::{server::TileProxy, Maptiler};
+
+fn proxy() -> TileProxy {
+    TileProxy::new(Maptiler::new("placeholder api key").unwrap())
+}
+
+#[tokio::test]
+async fn unknown_tileset() {
+    let request = Request::get("/not-a-tileset/0/0/0.png")
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(
+        proxy().handle(request).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn wrong_extension() {
+    // Satellite tiles are JPEGs
+    let request = Request::get("/satellite/0/0/0.png")
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(
+        proxy().handle(request).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn zoom_out_of_range() {
+    let request = Request::get("/satellite/21/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(
+        proxy().handle(request).await.status(),
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn address_in_use() {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    proxy()
+        .serve(addr)
+        .await
+        .expect_err("Served on an address that was already bound");
+}
@@ -0,0 +1,96 @@
+use maptiler_cloud::{style, Maptiler, StyleId, StyleRequest};
+use serde_json::json;
+
+#[test]
+fn localize() {
+    let mut style = json!({
+        "version": 8,
+        "sources": {
+            "maptiler_planet": {
+                "type": "vector",
+                "url": "https://api.maptiler.com/tiles/v3/tiles.json?key=KEY"
+            },
+            "hillshade": {
+                "type": "raster-dem",
+                "url": "https://api.maptiler.com/tiles/hillshades/tiles.json?key=KEY",
+                "maxzoom": 10
+            },
+            "custom": {
+                "type": "vector",
+                "url": "https://api.maptiler.com/tiles/0d8a8b5c/tiles.json?key=KEY"
+            },
+            "elsewhere": {
+                "type": "raster",
+                "tiles": ["https://tile.example.com/{z}/{x}/{y}.png?key=KEY"]
+            }
+        },
+        "glyphs": "https://api.maptiler.com/fonts/{fontstack}/{range}.pbf?key=KEY",
+        "sprite": [
+            {
+                "id": "default",
+                "url": "https://api.maptiler.com/maps/streets-v2/sprite?key=KEY&v=2"
+            }
+        ]
+    });
+
+    style::localize(
+        &mut style,
+        "https://api.maptiler.com",
+        "http://localhost:8080",
+    );
+
+    assert_eq!(
+        style["sources"]["maptiler_planet"],
+        json!({
+            "type": "vector",
+            "tiles": ["http://localhost:8080/v3/{z}/{x}/{y}.pbf"],
+            "minzoom": 0,
+            "maxzoom": 14
+        })
+    );
+    // Zoom ranges already in the style win
+    assert_eq!(style["sources"]["hillshade"]["maxzoom"], 10);
+    assert_eq!(
+        style["sources"]["hillshade"]["tiles"][0],
+        "http://localhost:8080/hillshades/{z}/{x}/{y}.png"
+    );
+    // Tilesets the proxy doesn't know keep their path
+    assert_eq!(
+        style["sources"]["custom"]["url"],
+        "http://localhost:8080/tiles/0d8a8b5c/tiles.json"
+    );
+    assert_eq!(
+        style["sources"]["elsewhere"]["tiles"][0],
+        "https://tile.example.com/{z}/{x}/{y}.png?key=KEY"
+    );
+    assert_eq!(
+        style["glyphs"],
+        "http://localhost:8080/fonts/{fontstack}/{range}.pbf"
+    );
+    assert_eq!(
+        style["sprite"][0]["url"],
+        "http://localhost:8080/maps/streets-v2/sprite?v=2"
+    );
+}
+
+#[test]
+fn localize_single_sprite() {
+    let mut style = json!({
+        "sprite": "https://api.maptiler.com/maps/streets-v2/sprite?key=KEY"
+    });
+
+    style::localize(&mut style, "https://api.maptiler.com", "/bundle");
+
+    assert_eq!(style["sprite"], "/bundle/maps/streets-v2/sprite");
+}
+
+#[test]
+fn style_request() {
+    let maptiler = Maptiler::new("KEY").unwrap();
+    let style_request = StyleRequest::new(StyleId::new("streets-v2").unwrap());
+
+    assert_eq!(
+        maptiler.create_request(style_request).dry_run().url,
+        "https://api.maptiler.com/maps/streets-v2/style.json?key=KEY"
+    );
+}
@@ -0,0 +1,49 @@
+use maptiler_cloud::terrain;
+
+// Every combination of bytes that matters, spread over more pixels than fit in one SIMD step
+fn pixels(count: usize, channels: usize) -> Vec<u8> {
+    (0..count * channels)
+        .map(|i| (i as u32).wrapping_mul(2_654_435_761).to_le_bytes()[3])
+        .collect()
+}
+
+#[test]
+fn elevation() {
+    assert_eq!(terrain::elevation(0, 0, 0), -10000.0);
+    assert_eq!(terrain::elevation(1, 134, 160), 0.0);
+    assert!((terrain::elevation(255, 255, 255) - 1_667_721.5).abs() < 0.1);
+}
+
+#[test]
+fn decode_rgb() {
+    for count in [0, 1, 3, 4, 5, 7, 8, 65_536] {
+        let pixels = pixels(count, 3);
+        let mut elevations = vec![f32::NAN; count];
+
+        terrain::decode_rgb(&pixels, &mut elevations);
+
+        for (pixel, elevation) in pixels.chunks_exact(3).zip(&elevations) {
+            assert_eq!(*elevation, terrain::elevation(pixel[0], pixel[1], pixel[2]));
+        }
+    }
+}
+
+#[test]
+fn decode_rgba() {
+    for count in [0, 1, 3, 4, 5, 7, 8, 65_536] {
+        let pixels = pixels(count, 4);
+        let mut elevations = vec![f32::NAN; count];
+
+        terrain::decode_rgba(&pixels, &mut elevations);
+
+        for (pixel, elevation) in pixels.chunks_exact(4).zip(&elevations) {
+            assert_eq!(*elevation, terrain::elevation(pixel[0], pixel[1], pixel[2]));
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn decode_wrong_length() {
+    terrain::decode_rgb(&[0; 7], &mut [0.0; 2]);
+}
@@ -0,0 +1,156 @@
+use maptiler_cloud::{
+    viewport::{Prefetcher, Viewport},
+    Maptiler, Priority, TileId, TileRequest, TileSet, TileX, TileY, Zoom,
+};
+
+#[test]
+fn tile_ids() {
+    let tile_request = TileRequest::new(TileSet::Satellite, 5, 3, 3).unwrap();
+    let tile_id = tile_request.tile_id().unwrap();
+
+    assert_eq!((tile_id.zoom(), tile_id.x(), tile_id.y()), (3, 5, 3));
+    assert_eq!(tile_id.tile_request(TileSet::Satellite), Ok(tile_request));
+    assert_eq!(TileId::from_u64(tile_id.as_u64()), Some(tile_id));
+
+    // Past the edge of the map, or deeper than a TileId can hold
+    assert_eq!(TileId::new(3, 8, 0), None);
+    assert_eq!(TileId::new(TileId::MAX_ZOOM + 1, 0, 0), None);
+    assert_eq!(TileId::from_u64(u64::MAX), None);
+
+    let deepest = TileId::new(TileId::MAX_ZOOM, (1 << 29) - 1, 12345).unwrap();
+    assert_eq!((deepest.x(), deepest.y()), ((1 << 29) - 1, 12345));
+}
+
+#[test]
+fn tile_id_order() {
+    let mut tile_ids: Vec<TileId> = [(1, 1, 1), (1, 0, 1), (0, 0, 0), (1, 1, 0), (1, 0, 0)]
+        .iter()
+        .map(|&(zoom, x, y)| TileId::new(zoom, x, y).unwrap())
+        .collect();
+
+    tile_ids.sort();
+
+    let sorted: Vec<(u32, u32, u32)> = tile_ids
+        .iter()
+        .map(|tile_id| (tile_id.zoom(), tile_id.x(), tile_id.y()))
+        .collect();
+
+    // Zoom level first, then Z-order
+    assert_eq!(
+        sorted,
+        vec![(0, 0, 0), (1, 0, 0), (1, 1, 0), (1, 0, 1), (1, 1, 1)]
+    );
+}
+
+#[test]
+fn viewport_tiles() {
+    let viewport = Viewport {
+        latitude: 0.0,
+        longitude: 0.0,
+        zoom: 1,
+        width: 256,
+        height: 256,
+        tile_size: 256,
+    };
+
+    let tiles: Vec<(u32, u32, f64, f64)> = viewport
+        .tiles(TileSet::Satellite)
+        .unwrap()
+        .iter()
+        .map(|tile| {
+            (
+                tile.request.x(),
+                tile.request.y(),
+                tile.offset_x,
+                tile.offset_y,
+            )
+        })
+        .collect();
+
+    // The center of the world is where the four tiles of zoom level 1 meet
+    assert_eq!(
+        tiles,
+        vec![
+            (0, 0, -128.0, -128.0),
+            (1, 0, 128.0, -128.0),
+            (0, 1, -128.0, 128.0),
+            (1, 1, 128.0, 128.0),
+        ]
+    );
+}
+
+#[test]
+fn viewport_wraps_around() {
+    let viewport = Viewport {
+        latitude: 0.0,
+        longitude: 180.0,
+        zoom: 0,
+        width: 256,
+        height: 256,
+        tile_size: 256,
+    };
+
+    let tiles = viewport.tiles(TileSet::Satellite).unwrap();
+
+    // The antimeridian is in the middle, so the one tile of zoom level 0 is shown twice
+    assert_eq!(tiles.len(), 2);
+    assert!(tiles.iter().all(|tile| tile.request.x() == 0));
+    assert_eq!((tiles[0].offset_x, tiles[1].offset_x), (-128.0, 128.0));
+}
+
+#[test]
+fn prefetcher() {
+    let maptiler = Maptiler::new("KEY").unwrap();
+    let mut prefetcher = Prefetcher::new(maptiler, TileSet::Satellite, 1);
+
+    let mut viewport = Viewport {
+        latitude: 0.0,
+        longitude: 0.0,
+        zoom: 3,
+        width: 256,
+        height: 256,
+        tile_size: 256,
+    };
+
+    // 2x2 tiles on screen, and a ring of 12 around them
+    let requests = prefetcher.update(&viewport).unwrap();
+    let priorities: Vec<Priority> = requests.iter().map(|r| r.priority()).collect();
+    assert_eq!(priorities[..4], [Priority::High; 4]);
+    assert_eq!(priorities[4..], [Priority::Low; 12]);
+
+    // Moving one tile east brings in a new column on the east side of the ring
+    viewport.longitude += 45.0;
+    let mut urls: Vec<String> = prefetcher
+        .update(&viewport)
+        .unwrap()
+        .iter()
+        .map(|request| request.dry_run().url)
+        .collect();
+    urls.sort();
+
+    assert_eq!(
+        urls,
+        (2..6)
+            .map(|y| format!(
+                "https://api.maptiler.com/tiles/satellite/3/6/{}.jpg?key=KEY",
+                y
+            ))
+            .collect::<Vec<String>>()
+    );
+}
+
+#[test]
+fn typed_coordinates() {
+    let zoom = Zoom::new(3);
+    let (x, y) = (TileX::new(5), TileY::new(2));
+
+    let typed = TileRequest::new(TileSet::Satellite, x, y, zoom).unwrap();
+    let plain = TileRequest::new(TileSet::Satellite, 5, 2, 3).unwrap();
+    assert_eq!(typed, plain);
+
+    assert_eq!(TileId::new(zoom, x, y), TileId::new(3, 5, 2));
+    assert_eq!(u32::from(zoom), 3);
+    assert_eq!(TileX::from(7).get(), 7);
+    assert_eq!(u32::from(TileY::new(4)), 4);
+    assert_eq!(zoom.to_string(), "3");
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "tower")]
+
+use std::{
+    future::poll_fn,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http::{HeaderMap, StatusCode};
+use maptiler_cloud::{
+    backend::{BackendError, BoxFuture, HttpBackend, HttpRequest, HttpResponse, ResponseBody},
+    Maptiler, TileJsonRequest, TileRequest, TileSet,
+};
+use tower_service::Service;
+
+const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x00, 0xFF, 0xD9];
+
+// A backend that answers every request with the same body, and records the URLs requested
+#[derive(Default)]
+struct RecordingBackend {
+    urls: Mutex<Vec<String>>,
+}
+
+struct OneChunk(Option<Bytes>);
+
+impl ResponseBody for OneChunk {
+    fn chunk(&mut self) -> BoxFuture<'_, Result<Option<Bytes>, BackendError>> {
+        let chunk = self.0.take();
+        Box::pin(async move { Ok(chunk) })
+    }
+}
+
+impl HttpBackend for RecordingBackend {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, BackendError>> {
+        self.urls.lock().unwrap().push(request.url);
+
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: Box::new(OneChunk(Some(Bytes::from_static(JPEG)))),
+            })
+        })
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+#[tokio::test]
+async fn tower_service() {
+    let backend = Arc::new(RecordingBackend::default());
+    let mut maptiler = Maptiler::builder("KEY")
+        .backend(backend.clone())
+        .build()
+        .unwrap();
+
+    poll_fn(|cx| Service::<TileRequest>::poll_ready(&mut maptiler, cx))
+        .await
+        .unwrap();
+
+    let tile_request = TileRequest::new(TileSet::Satellite, 0, 0, 0).unwrap();
+    assert_eq!(maptiler.call(tile_request).await.unwrap(), JPEG);
+
+    maptiler
+        .call(TileJsonRequest::new(TileSet::Outdoor))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *backend.urls.lock().unwrap(),
+        [
+            "https://api.maptiler.com/tiles/satellite/0/0/0.jpg?key=KEY",
+            "https://api.maptiler.com/tiles/outdoor/tiles.json?key=KEY",
+        ]
+    );
+}
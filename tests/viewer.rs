@@ -0,0 +1,47 @@
+use maptiler_cloud::{viewer::Viewer, TileJson};
+
+#[test]
+fn viewer_html() {
+    let html = Viewer::new("tiles/{z}/{x}/{y}.jpg", 2, 12)
+        .bounds([5.9, 45.8, 10.5, 47.8])
+        .title("Alps & <lakes>")
+        .attribution("</script><script>alert(1)</script>")
+        .to_html();
+
+    assert!(html.contains("<title>Alps &amp; &lt;lakes&gt;</title>"));
+    assert!(html.contains(r#"L.tileLayer("tiles/{z}/{x}/{y}.jpg""#));
+    assert!(html.contains("L.latLngBounds([45.8, 5.9], [47.8, 10.5])"));
+    assert!(html.contains("minZoom: 2, maxZoom: 12"));
+    assert!(html.contains("map.fitBounds(bounds);"));
+
+    // The attribution can't break out of the script
+    assert_eq!(html.matches("</script>").count(), 2);
+}
+
+#[test]
+fn viewer_from_tilejson() {
+    let tilejson = TileJson::from_slice(
+        br#"{"name": "Satellite", "minzoom": 0, "maxzoom": 20, "attribution": "MapTiler"}"#,
+    )
+    .unwrap();
+
+    let html = Viewer::from_tilejson("http://localhost:8080/satellite/{z}/{x}/{y}.jpg", &tilejson)
+        .to_html();
+
+    assert!(html.contains("<title>Satellite</title>"));
+    assert!(html.contains("maxZoom: 20"));
+    assert!(html.contains(r#"attribution: "MapTiler""#));
+    assert!(html.contains("map.fitWorld();"));
+    assert!(html.contains(r#"src="https://unpkg.com/leaflet@1.9.4/dist/leaflet.js""#));
+}
+
+#[test]
+fn viewer_offline() {
+    let html = Viewer::new("tiles/{z}/{x}/{y}.jpg", 0, 5)
+        .leaflet_url("leaflet/")
+        .to_html();
+
+    assert!(html.contains(r#"<link rel="stylesheet" href="leaflet/leaflet.css">"#));
+    assert!(html.contains(r#"<script src="leaflet/leaflet.js"></script>"#));
+    assert!(!html.contains("unpkg"));
+}